@@ -0,0 +1,165 @@
+use crate::game::count::Count;
+use crate::game::money::Money;
+use crate::game::rules::TableRules;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+/**
+ * What a player can see when placing a bet
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct BetSituation<'a> {
+    pub name: &'a str,
+    pub bankroll: Money,
+    pub rules: &'a TableRules,
+    // Every count the table keeps, one per system
+    pub counts: &'a [Count],
+}
+
+/**
+ * How much a computer player bets each round. The dealer keeps the
+ * wager within the table limits and the player's bankroll
+ */
+pub trait BettingStrategy: fmt::Debug {
+    fn wager(&mut self, bet: &BetSituation) -> Money;
+}
+
+/**
+ * The same bet every round
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlatBet(pub Money);
+impl BettingStrategy for FlatBet {
+    fn wager(&mut self, bet: &BetSituation) -> Money {
+        self.0.min(bet.bankroll)
+    }
+}
+
+/**
+ * A random bet in whole dollars of up to about 5% of the bankroll, never
+ * below the table minimum
+ */
+#[derive(Debug, Clone)]
+pub struct RandomBet {
+    pub rng: ChaCha8Rng,
+}
+impl RandomBet {
+    pub fn new(seed: u64) -> Self {
+        RandomBet {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+impl BettingStrategy for RandomBet {
+    fn wager(&mut self, bet: &BetSituation) -> Money {
+        let pct: f64 = 0.05;
+        // The smallest whole dollar amount covering the minimum, which may
+        // have cents
+        let lower = (bet.rules.min_bet.cents() + 99).div_euclid(100);
+        let target = bet.bankroll.dollars() as f64 * pct;
+        let mut upper = target.floor() as i64;
+        if upper <= lower {
+            upper = (lower * 2).max(lower + 1);
+        }
+        let wager = Money::from_dollars(self.rng.gen_range(lower..upper));
+        wager.min(bet.bankroll).min(bet.rules.max_bet)
+    }
+}
+
+/**
+ * A counter's bet: one unit, and another for every point the count is
+ * above one, up to `max_units`.
+ *
+ * Balanced systems bet off the true count. Unbalanced systems bet off the
+ * running count, which their initial running count already lines up with
+ * the pivot (KO +4 plays like a true count of +4), so it isn't divided by
+ * the decks left. Bets one unit if the table doesn't keep the named
+ * system's count
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountBet {
+    pub system: String,
+    pub unit: Money,
+    pub max_units: i64,
+}
+impl BettingStrategy for CountBet {
+    fn wager(&mut self, bet: &BetSituation) -> Money {
+        let count = bet
+            .counts
+            .iter()
+            .find(|count| count.system.name == self.system);
+        let points = count.map_or(0.0, |count| {
+            if count.system.balanced() {
+                count.true_count()
+            } else {
+                count.running
+            }
+        });
+        let units = (points.floor() as i64 - 1).clamp(1, self.max_units.max(1));
+        (self.unit * units).min(bet.bankroll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::counting::CountingSystem;
+
+    fn wager(system: &str, running: f64) -> Money {
+        let rules = TableRules::default();
+        let mut count = Count::new(CountingSystem::from_name(system).unwrap(), 6);
+        count.running = running;
+        let counts = [count];
+        let bet = BetSituation {
+            name: "Counter",
+            bankroll: Money::from_dollars(1000),
+            rules: &rules,
+            counts: &counts,
+        };
+        let mut betting = CountBet {
+            system: counts[0].system.name.clone(),
+            unit: Money::from_dollars(10),
+            max_units: 8,
+        };
+        betting.wager(&bet)
+    }
+
+    #[test]
+    fn balanced_counts_spread_on_the_true_count() {
+        // Six decks left, so +24 is a true count of +4
+        assert_eq!(wager("hi-lo", 24.0), Money::from_dollars(30));
+        assert_eq!(wager("hi-lo", 4.0), Money::from_dollars(10));
+    }
+
+    #[test]
+    fn unbalanced_counts_spread_on_the_running_count() {
+        assert_eq!(wager("ko", -20.0), Money::from_dollars(10));
+        assert_eq!(wager("ko", 4.0), Money::from_dollars(30));
+        assert_eq!(wager("ko", 20.0), Money::from_dollars(80));
+    }
+
+    #[test]
+    fn random_bets_cover_a_minimum_with_cents() {
+        for min_bet in ["0", "0.50", "7.50"] {
+            let rules = TableRules {
+                min_bet: min_bet.parse().unwrap(),
+                ..TableRules::default()
+            };
+            let bet = BetSituation {
+                name: "Random",
+                bankroll: Money::from_dollars(100),
+                rules: &rules,
+                counts: &[],
+            };
+            let mut betting = RandomBet::new(3);
+            for _ in 0..100 {
+                let wager = betting.wager(&bet);
+                assert!(wager >= rules.min_bet, "{} is below the minimum", wager);
+                assert!(wager <= rules.max_bet, "{} is over the maximum", wager);
+                assert_eq!(wager.cents() % 100, 0);
+            }
+        }
+    }
+}
@@ -1,7 +1,300 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Suit::Clubs => "Clubs",
+            Suit::Diamonds => "Diamonds",
+            Suit::Hearts => "Hearts",
+            Suit::Spades => "Spades",
+        }
+    }
+    /**
+     * Single letter used in short notation
+     */
+    pub fn symbol(self) -> char {
+        match self {
+            Suit::Clubs => 'C',
+            Suit::Diamonds => 'D',
+            Suit::Hearts => 'H',
+            Suit::Spades => 'S',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    Ace,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+    ];
+
+    /**
+     * Blackjack value, counting an Ace as 1
+     */
+    pub fn value(self) -> u8 {
+        match self {
+            Rank::Jack | Rank::Queen | Rank::King => 10,
+            rank => rank as u8 + 1,
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Rank::Ace => "Ace",
+            Rank::Two => "Two",
+            Rank::Three => "Three",
+            Rank::Four => "Four",
+            Rank::Five => "Five",
+            Rank::Six => "Six",
+            Rank::Seven => "Seven",
+            Rank::Eight => "Eight",
+            Rank::Nine => "Nine",
+            Rank::Ten => "Ten",
+            Rank::Jack => "Jack",
+            Rank::Queen => "Queen",
+            Rank::King => "King",
+        }
+    }
+    /**
+     * Single character used in short notation, with T for Ten
+     */
+    pub fn symbol(self) -> char {
+        match self {
+            Rank::Ace => 'A',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
+            rank => (b'1' + rank as u8) as char,
+        }
+    }
+}
+
+/**
+ * A card packed into a single byte: rank * 4 + suit
+ */
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Card(u8);
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Self {
+        Card(rank as u8 * 4 + suit as u8)
+    }
+    pub fn rank(self) -> Rank {
+        Rank::ALL[(self.0 / 4) as usize]
+    }
+    pub fn suit(self) -> Suit {
+        Suit::ALL[(self.0 % 4) as usize]
+    }
+    pub fn value(self) -> u8 {
+        self.rank().value()
+    }
+    pub fn is_ace(self) -> bool {
+        self.rank() == Rank::Ace
+    }
+}
+impl fmt::Debug for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Card({:?}, {:?})", self.rank(), self.suit())
+    }
+}
+impl fmt::Display for Card {
+    /**
+     * Short notation such as "AS" or "TD", or with {:#} the long form
+     * such as "Ace of Spades"
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{} of {}", self.rank().name(), self.suit().name())
+        } else {
+            write!(f, "{}{}", self.rank().symbol(), self.suit().symbol())
+        }
+    }
+}
+
+/**
+ * Why a piece of card notation couldn't be read
+ */
 #[derive(Debug, Clone, PartialEq)]
-pub struct Card {
-    pub suit: String,
-    pub face: String,
-    pub value: u8,
+pub enum ParseCardError {
+    Empty,
+    Incomplete(String),
+    UnknownRank(String),
+    UnknownSuit(String),
+    // Space-separated cards between the commas of a long-form hand
+    SeveralCards(String),
+}
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCardError::Empty => write!(f, "expected a card, found nothing"),
+            ParseCardError::Incomplete(card) => {
+                write!(f, "\"{}\" needs a rank and a suit, e.g. AS or 10H", card)
+            }
+            ParseCardError::UnknownRank(card) => write!(
+                f,
+                "unknown rank in \"{}\", expected A, 2-9, T (or 10), J, Q or K",
+                card
+            ),
+            ParseCardError::UnknownSuit(card) => write!(
+                f,
+                "unknown suit in \"{}\", expected C, D, H or S",
+                card
+            ),
+            ParseCardError::SeveralCards(cards) => write!(
+                f,
+                "\"{}\" is more than one card, separate long-form cards with commas \
+                 and short ones with spaces",
+                cards
+            ),
+        }
+    }
+}
+impl std::error::Error for ParseCardError {}
+impl FromStr for Card {
+    type Err = ParseCardError;
+    /**
+     * Parse short notation ("As", "TD", "10h") or the long form
+     * ("Ace of Spades"), ignoring case
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let card = s.trim();
+        if card.is_empty() {
+            return Err(ParseCardError::Empty);
+        }
+        let unknown_rank = || ParseCardError::UnknownRank(String::from(card));
+        let unknown_suit = || ParseCardError::UnknownSuit(String::from(card));
+        let long_form = card.to_ascii_lowercase();
+        if let Some((rank, suit)) = long_form.split_once(" of ") {
+            let rank = Rank::ALL
+                .into_iter()
+                .find(|r| r.name().eq_ignore_ascii_case(rank.trim()))
+                .ok_or_else(unknown_rank)?;
+            let suit = Suit::ALL
+                .into_iter()
+                .find(|s| s.name().eq_ignore_ascii_case(suit.trim()))
+                .ok_or_else(unknown_suit)?;
+            return Ok(Card::new(rank, suit));
+        }
+        if card.chars().count() < 2 {
+            return Err(ParseCardError::Incomplete(String::from(card)));
+        }
+        // The suit is always the last character
+        let split = card.char_indices().last().map(|(i, _)| i).unwrap_or(0);
+        let (rank, suit) = card.split_at(split);
+        let rank = match rank.to_ascii_uppercase().as_str() {
+            "10" => Rank::Ten,
+            r if r.chars().count() == 1 => Rank::ALL
+                .into_iter()
+                .find(|rank| r.starts_with(rank.symbol()))
+                .ok_or_else(unknown_rank)?,
+            _ => return Err(unknown_rank()),
+        };
+        let suit = Suit::ALL
+            .into_iter()
+            .find(|s| suit.eq_ignore_ascii_case(&s.symbol().to_string()))
+            .ok_or_else(unknown_suit)?;
+        Ok(Card::new(rank, suit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_short_notation_in_any_case() {
+        let ace = Card::new(Rank::Ace, Suit::Spades);
+        assert_eq!("As".parse(), Ok(ace));
+        assert_eq!("AS".parse(), Ok(ace));
+        assert_eq!(" as ".parse(), Ok(ace));
+        assert_eq!("Td".parse(), Ok(Card::new(Rank::Ten, Suit::Diamonds)));
+        assert_eq!("10h".parse(), Ok(Card::new(Rank::Ten, Suit::Hearts)));
+        assert_eq!("qc".parse(), Ok(Card::new(Rank::Queen, Suit::Clubs)));
+    }
+
+    #[test]
+    fn parses_the_long_form() {
+        assert_eq!(
+            "Ace of Spades".parse(),
+            Ok(Card::new(Rank::Ace, Suit::Spades))
+        );
+        assert_eq!(
+            "seven OF hearts".parse(),
+            Ok(Card::new(Rank::Seven, Suit::Hearts))
+        );
+    }
+
+    #[test]
+    fn every_card_reads_back_what_it_prints() {
+        for rank in Rank::ALL {
+            for suit in Suit::ALL {
+                let card = Card::new(rank, suit);
+                assert_eq!(card.to_string().parse(), Ok(card));
+                assert_eq!(format!("{:#}", card).parse(), Ok(card));
+            }
+        }
+        assert_eq!(Card::new(Rank::Ten, Suit::Diamonds).to_string(), "TD");
+        assert_eq!(
+            format!("{:#}", Card::new(Rank::Ace, Suit::Spades)),
+            "Ace of Spades"
+        );
+    }
+
+    #[test]
+    fn says_what_is_wrong_with_bad_notation() {
+        let parse = |s: &str| s.parse::<Card>().unwrap_err();
+        assert_eq!(parse(""), ParseCardError::Empty);
+        assert_eq!(parse("  "), ParseCardError::Empty);
+        assert_eq!(parse("A"), ParseCardError::Incomplete(String::from("A")));
+        assert_eq!(parse("Xs"), ParseCardError::UnknownRank(String::from("Xs")));
+        assert_eq!(
+            parse("11s"),
+            ParseCardError::UnknownRank(String::from("11s"))
+        );
+        assert_eq!(parse("Ax"), ParseCardError::UnknownSuit(String::from("Ax")));
+        assert_eq!(
+            parse("Joker of Spades"),
+            ParseCardError::UnknownRank(String::from("Joker of Spades"))
+        );
+        assert_eq!(
+            parse("Ace of Stars"),
+            ParseCardError::UnknownSuit(String::from("Ace of Stars"))
+        );
+    }
 }
-impl Card {}
@@ -0,0 +1,422 @@
+use crate::game::strategy::{BasicStrategy, HandKind, Play, UPCARDS};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/**
+ * Why a strategy chart couldn't be loaded
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartError {
+    Io(String),
+    // The file isn't valid CSV or TOML
+    Syntax(String),
+    UnknownFormat(String),
+    UnknownKind(String),
+    UnknownHand(HandKind, String),
+    UnknownUpcard(String),
+    UnknownPlay(HandKind, u8, u8, String),
+    TooManyCells(HandKind, u8),
+    DuplicateCell(HandKind, u8, u8),
+    MissingCells(Vec<(HandKind, u8, u8)>),
+    // Where in a CSV file the error is
+    Line(usize, Box<ChartError>),
+}
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChartError::Io(error) => write!(f, "couldn't read the chart: {}", error),
+            ChartError::Syntax(error) => write!(f, "{}", error),
+            ChartError::UnknownFormat(path) => {
+                write!(
+                    f,
+                    "unknown chart format for {}, expected .csv or .toml",
+                    path
+                )
+            }
+            ChartError::UnknownKind(kind) => {
+                write!(f, "unknown table \"{}\", expected hard, soft or pair", kind)
+            }
+            ChartError::UnknownHand(kind, hand) => {
+                let rows = kind.rows();
+                write!(
+                    f,
+                    "unknown {} hand \"{}\", expected {} to {}",
+                    kind.name(),
+                    hand,
+                    label(*kind, *rows.start()),
+                    label(*kind, *rows.end())
+                )
+            }
+            ChartError::UnknownUpcard(upcard) => {
+                write!(
+                    f,
+                    "unknown dealer upcard \"{}\", expected 2 to 10 or A",
+                    upcard
+                )
+            }
+            ChartError::UnknownPlay(kind, hand, upcard, code) => write!(
+                f,
+                "{}: unknown play \"{}\", expected H, S, Dh, Ds, P, Rh, Rs or Rp",
+                cell(*kind, *hand, *upcard),
+                code
+            ),
+            ChartError::TooManyCells(kind, hand) => write!(
+                f,
+                "{} {} has more than one cell per upcard",
+                kind.name(),
+                label(*kind, *hand)
+            ),
+            ChartError::DuplicateCell(kind, hand, upcard) => {
+                write!(f, "{} is given twice", cell(*kind, *hand, *upcard))
+            }
+            ChartError::MissingCells(cells) => {
+                // A row missing altogether is named once
+                let whole_row = |kind: HandKind, hand: u8| {
+                    let row = cells.iter().filter(|(k, h, _)| (*k, *h) == (kind, hand));
+                    row.count() == UPCARDS.count()
+                };
+                let mut missing: Vec<String> = Vec::new();
+                for (kind, hand, upcard) in cells {
+                    if !whole_row(*kind, *hand) {
+                        missing.push(cell(*kind, *hand, *upcard));
+                    } else if *upcard == *UPCARDS.start() {
+                        missing.push(format!(
+                            "{} {} (whole row)",
+                            kind.name(),
+                            label(*kind, *hand)
+                        ));
+                    }
+                }
+                write!(f, "missing cells: {}", missing.join(", "))
+            }
+            ChartError::Line(line, error) => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+impl std::error::Error for ChartError {}
+
+/**
+ * Row or column heading for a hand or upcard: totals for hard and soft
+ * hands, the card for pairs and upcards
+ */
+fn label(kind: HandKind, hand: u8) -> String {
+    match (kind, hand) {
+        (HandKind::Pair, 11) => String::from("A"),
+        _ => hand.to_string(),
+    }
+}
+fn cell(kind: HandKind, hand: u8, upcard: u8) -> String {
+    format!(
+        "{} {} vs {}",
+        kind.name(),
+        label(kind, hand),
+        label(HandKind::Pair, upcard)
+    )
+}
+fn parse_card(card: &str) -> Option<u8> {
+    match card.trim().to_ascii_uppercase().as_str() {
+        "A" => Some(11),
+        "T" => Some(10),
+        number => number.parse::<u8>().ok().filter(|n| UPCARDS.contains(n)),
+    }
+}
+fn parse_hand(kind: HandKind, hand: &str) -> Result<u8, ChartError> {
+    let parsed = match kind {
+        HandKind::Pair => parse_card(hand),
+        _ => hand.trim().parse::<u8>().ok(),
+    };
+    parsed
+        .filter(|total| kind.rows().contains(total))
+        .ok_or_else(|| ChartError::UnknownHand(kind, String::from(hand.trim())))
+}
+
+impl BasicStrategy {
+    /**
+     * Load a chart, picking the format from the file extension
+     */
+    pub fn load(path: &Path) -> Result<Self, ChartError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ChartError::Io(e.to_string()))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => BasicStrategy::from_csv(&text),
+            Some("toml") => BasicStrategy::from_toml(&text),
+            _ => Err(ChartError::UnknownFormat(path.display().to_string())),
+        }
+    }
+    /**
+     * Read a chart with a header of dealer upcards, e.g.
+     *
+     * kind,hand,2,3,4,5,6,7,8,9,10,A
+     * hard,16,S,S,S,S,S,H,H,Rh,Rh,Rh
+     * pair,A,P,P,P,P,P,P,P,P,P,P
+     */
+    pub fn from_csv(text: &str) -> Result<Self, ChartError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(_, line)| !line.trim().is_empty());
+        let (number, header) = lines
+            .next()
+            .ok_or_else(|| ChartError::Syntax(String::from("the chart is empty")))?;
+        let upcards = header
+            .split(',')
+            .skip(2)
+            .map(|upcard| {
+                parse_card(upcard).ok_or_else(|| ChartError::UnknownUpcard(upcard.trim().into()))
+            })
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| ChartError::Line(number, Box::new(e)))?;
+        let mut chart = BasicStrategy {
+            plays: HashMap::new(),
+        };
+        for (number, line) in lines {
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            chart
+                .csv_row(&upcards, &cells)
+                .map_err(|e| ChartError::Line(number, Box::new(e)))?;
+        }
+        chart.validate()?;
+        Ok(chart)
+    }
+    /**
+     * Read a chart with one table per kind of hand and a row of plays
+     * against a dealer 2 through Ace, e.g.
+     *
+     * [hard]
+     * 16 = ["S", "S", "S", "S", "S", "H", "H", "Rh", "Rh", "Rh"]
+     */
+    pub fn from_toml(text: &str) -> Result<Self, ChartError> {
+        let tables = text
+            .parse::<toml::Table>()
+            .map_err(|e| ChartError::Syntax(e.to_string()))?;
+        let mut chart = BasicStrategy {
+            plays: HashMap::new(),
+        };
+        for (kind, rows) in &tables {
+            let kind =
+                HandKind::from_name(kind).ok_or_else(|| ChartError::UnknownKind(kind.clone()))?;
+            let rows = rows.as_table().ok_or_else(|| {
+                ChartError::Syntax(format!("[{}] should be a table of hands", kind.name()))
+            })?;
+            for (hand, codes) in rows {
+                let hand = parse_hand(kind, hand)?;
+                let codes = codes.as_array().ok_or_else(|| {
+                    ChartError::Syntax(format!(
+                        "{} {} should be a list of plays",
+                        kind.name(),
+                        label(kind, hand)
+                    ))
+                })?;
+                if codes.len() > UPCARDS.count() {
+                    return Err(ChartError::TooManyCells(kind, hand));
+                }
+                for (upcard, code) in UPCARDS.zip(codes) {
+                    let text = code.as_str().ok_or_else(|| {
+                        ChartError::UnknownPlay(kind, hand, upcard, code.to_string())
+                    })?;
+                    chart.insert(kind, hand, upcard, text)?;
+                }
+            }
+        }
+        chart.validate()?;
+        Ok(chart)
+    }
+    fn csv_row(&mut self, upcards: &[u8], cells: &[&str]) -> Result<(), ChartError> {
+        let [kind, hand, codes @ ..] = cells else {
+            return Err(ChartError::Syntax(String::from(
+                "expected a table, a hand and one play per upcard",
+            )));
+        };
+        let kind = HandKind::from_name(kind)
+            .ok_or_else(|| ChartError::UnknownKind(String::from(*kind)))?;
+        let hand = parse_hand(kind, hand)?;
+        if codes.len() > upcards.len() {
+            return Err(ChartError::TooManyCells(kind, hand));
+        }
+        for (upcard, code) in upcards.iter().zip(codes) {
+            self.insert(kind, hand, *upcard, code)?;
+        }
+        Ok(())
+    }
+    /**
+     * Add one cell. An empty cell is left out, to be reported as missing
+     */
+    fn insert(
+        &mut self,
+        kind: HandKind,
+        hand: u8,
+        upcard: u8,
+        code: &str,
+    ) -> Result<(), ChartError> {
+        if code.is_empty() {
+            return Ok(());
+        }
+        let play = Play::from_code(code)
+            .ok_or_else(|| ChartError::UnknownPlay(kind, hand, upcard, String::from(code)))?;
+        if self.plays.insert((kind, hand, upcard), play).is_some() {
+            return Err(ChartError::DuplicateCell(kind, hand, upcard));
+        }
+        Ok(())
+    }
+    /**
+     * Check every hand has a play against every upcard
+     */
+    pub fn validate(&self) -> Result<(), ChartError> {
+        let missing: Vec<(HandKind, u8, u8)> = self
+            .cells()
+            .filter(|key| !self.plays.contains_key(key))
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(ChartError::MissingCells(missing))
+        }
+    }
+    /**
+     * Every cell of a complete chart, row by row
+     */
+    fn cells(&self) -> impl Iterator<Item = (HandKind, u8, u8)> {
+        HandKind::ALL.into_iter().flat_map(|kind| {
+            kind.rows()
+                .flat_map(move |hand| UPCARDS.map(move |upcard| (kind, hand, upcard)))
+        })
+    }
+    fn codes(&self, kind: HandKind, hand: u8) -> Vec<&'static str> {
+        UPCARDS
+            .map(|upcard| {
+                self.plays
+                    .get(&(kind, hand, upcard))
+                    .map_or("", |play| play.code())
+            })
+            .collect()
+    }
+    pub fn to_csv(&self) -> String {
+        let upcards: Vec<String> = UPCARDS
+            .map(|upcard| label(HandKind::Pair, upcard))
+            .collect();
+        let mut csv = format!("kind,hand,{}\n", upcards.join(","));
+        for kind in HandKind::ALL {
+            for hand in kind.rows() {
+                csv += &format!(
+                    "{},{},{}\n",
+                    kind.name(),
+                    label(kind, hand),
+                    self.codes(kind, hand).join(",")
+                );
+            }
+        }
+        csv
+    }
+    pub fn to_toml(&self) -> String {
+        let mut toml = String::from("# Plays against a dealer 2, 3, 4, 5, 6, 7, 8, 9, 10 and A\n");
+        for kind in HandKind::ALL {
+            toml += &format!("\n[{}]\n", kind.name());
+            for hand in kind.rows() {
+                let codes: Vec<String> = self
+                    .codes(kind, hand)
+                    .iter()
+                    .map(|code| format!("\"{}\"", code))
+                    .collect();
+                toml += &format!("{} = [{}]\n", label(kind, hand), codes.join(", "));
+            }
+        }
+        toml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::TableRules;
+
+    fn charts() -> Vec<BasicStrategy> {
+        let presets = [
+            "standard",
+            "vegas-strip",
+            "downtown",
+            "atlantic-city",
+            "european",
+        ];
+        presets
+            .iter()
+            .map(|name| BasicStrategy::new(&TableRules::preset(name).unwrap()))
+            .collect()
+    }
+
+    /**
+     * Apply `edit` to the first line starting with `start`. Hard rows come
+     * first, so "16 = " is hard 16 in TOML
+     */
+    fn edit_line(text: &str, start: &str, edit: impl Fn(&str) -> String) -> String {
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        let line = lines
+            .iter_mut()
+            .find(|line| line.starts_with(start))
+            .unwrap();
+        *line = edit(line);
+        lines.join("\n")
+    }
+
+    #[test]
+    fn csv_reads_back_what_it_writes() {
+        for chart in charts() {
+            assert_eq!(BasicStrategy::from_csv(&chart.to_csv()), Ok(chart));
+        }
+    }
+
+    #[test]
+    fn toml_reads_back_what_it_writes() {
+        for chart in charts() {
+            assert_eq!(BasicStrategy::from_toml(&chart.to_toml()), Ok(chart));
+        }
+    }
+
+    #[test]
+    fn reports_missing_rows_and_cells() {
+        let csv = BasicStrategy::new(&TableRules::default()).to_csv();
+        let csv = edit_line(&csv, "hard,16,", |_| String::new());
+        let csv = edit_line(&csv, "pair,A,", |line| {
+            let (row, _) = line.rsplit_once(',').unwrap();
+            format!("{},", row)
+        });
+        let error = BasicStrategy::from_csv(&csv).unwrap_err();
+        let mut missing: Vec<(HandKind, u8, u8)> =
+            UPCARDS.map(|upcard| (HandKind::Hard, 16, upcard)).collect();
+        missing.push((HandKind::Pair, 11, 11));
+        assert_eq!(error, ChartError::MissingCells(missing));
+        assert_eq!(
+            error.to_string(),
+            "missing cells: hard 16 (whole row), pair A vs A"
+        );
+        let toml = BasicStrategy::new(&TableRules::default()).to_toml();
+        let toml = edit_line(&toml, "16 = ", |_| String::new());
+        assert_eq!(
+            BasicStrategy::from_toml(&toml),
+            Err(ChartError::MissingCells(
+                UPCARDS.map(|upcard| (HandKind::Hard, 16, upcard)).collect()
+            ))
+        );
+    }
+
+    #[test]
+    fn reports_unknown_plays_with_their_cell() {
+        let chart = BasicStrategy::new(&TableRules::default());
+        let csv = edit_line(&chart.to_csv(), "hard,16,", |line| {
+            line.replacen(",S,", ",X,", 1)
+        });
+        let error = BasicStrategy::from_csv(&csv).unwrap_err();
+        // The header, then hard 5 to 15 come before hard 16
+        let unknown = ChartError::UnknownPlay(HandKind::Hard, 16, 2, String::from("X"));
+        assert_eq!(error, ChartError::Line(13, Box::new(unknown.clone())));
+        assert_eq!(
+            error.to_string(),
+            "line 13: hard 16 vs 2: unknown play \"X\", expected H, S, Dh, Ds, P, Rh, Rs or Rp"
+        );
+        let toml = edit_line(&chart.to_toml(), "16 = ", |line| {
+            line.replacen("\"S\"", "\"X\"", 1)
+        });
+        assert_eq!(BasicStrategy::from_toml(&toml), Err(unknown));
+    }
+}
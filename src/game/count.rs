@@ -0,0 +1,181 @@
+use crate::game::card::Card;
+use crate::game::counting::CountingSystem;
+
+use std::fmt;
+
+const CARDS_PER_DECK: f64 = 52.0;
+
+/**
+ * Count of every card seen since the shoe was last shuffled, kept with
+ * one counting system
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Count {
+    pub system: CountingSystem,
+    pub decks: u8,
+    pub running: f64,
+    pub seen: usize,
+    pub aces_seen: usize,
+    // Cards in the shoe once it was shuffled and burned
+    pub shoe: usize,
+}
+impl Count {
+    pub fn new(system: CountingSystem, decks: u8) -> Self {
+        Count {
+            running: system.irc(decks),
+            system,
+            decks,
+            seen: 0,
+            aces_seen: 0,
+            shoe: usize::from(decks) * CARDS_PER_DECK as usize,
+        }
+    }
+    /**
+     * Start over on a freshly shuffled shoe
+     */
+    pub fn reset(&mut self, shoe: usize) {
+        self.running = self.system.irc(self.decks);
+        self.seen = 0;
+        self.aces_seen = 0;
+        self.shoe = shoe;
+    }
+    pub fn see(&mut self, card: Card) {
+        self.running += self.system.tag(card);
+        self.seen += 1;
+        if card.is_ace() {
+            self.aces_seen += 1;
+        }
+    }
+    /**
+     * Decks not yet seen, never less than a single card's worth
+     */
+    pub fn decks_remaining(&self) -> f64 {
+        let cards = self.shoe.saturating_sub(self.seen).max(1);
+        cards as f64 / CARDS_PER_DECK
+    }
+    /**
+     * Running count per deck remaining. Unbalanced systems are played off
+     * the running count instead
+     */
+    pub fn true_count(&self) -> f64 {
+        self.running / self.decks_remaining()
+    }
+    pub fn aces_remaining(&self) -> usize {
+        (usize::from(self.decks) * 4).saturating_sub(self.aces_seen)
+    }
+}
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} running count {:+}", self.system.name, self.running)?;
+        if self.system.balanced() {
+            write!(f, ", true count {:+.1}", self.true_count())?;
+        }
+        if self.system.ace_side_count {
+            write!(f, ", {} aces left", self.aces_remaining())?;
+        }
+        write!(f, " ({:.1} decks left)", self.decks_remaining())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::betting::FlatBet;
+    use crate::game::dealer::Dealer;
+    use crate::game::hand::Hand;
+    use crate::game::money::Money;
+    use crate::game::player::Player;
+    use crate::game::rules::{ShoeMode, TableRules};
+    use crate::game::strategy::ChartStrategy;
+    use crate::game::ui::{ScriptedInput, SilentOutput, Ui};
+    use crate::game::{Game, GameState};
+
+    fn quiet_dealer(rules: TableRules) -> Dealer {
+        let mut dealer = Dealer::new(rules, 1).unwrap();
+        dealer.ui = Ui::new(Box::new(ScriptedInput::default()), Box::new(SilentOutput));
+        dealer
+    }
+
+    #[test]
+    fn true_count_divides_by_the_decks_not_yet_seen() {
+        let mut count = Count::new(CountingSystem::hi_lo(), 6);
+        assert_eq!(count.decks_remaining(), 6.0);
+        for _ in 0..52 {
+            count.see("2h".parse().unwrap());
+        }
+        assert_eq!(count.running, 52.0);
+        assert_eq!(count.decks_remaining(), 5.0);
+        assert_eq!(count.true_count(), 10.4);
+        // Past the end of the shoe a single card is left, not none
+        count.seen = count.shoe + 10;
+        assert_eq!(count.decks_remaining(), 1.0 / 52.0);
+        assert_eq!(count.true_count(), 52.0 * 52.0);
+    }
+
+    #[test]
+    fn a_shuffle_starts_over_without_the_burn_cards() {
+        let rules = TableRules {
+            burn_cards: 5,
+            ..TableRules::default()
+        };
+        let mut dealer = quiet_dealer(rules);
+        dealer.shuffle();
+        let mut hand = Hand::new();
+        for _ in 0..10 {
+            dealer.deal_card(&mut hand).unwrap();
+        }
+        assert_eq!(dealer.counts[0].seen, 10);
+        dealer.shoe.discard(hand.clear());
+        dealer.shuffle();
+        let count = &dealer.counts[0];
+        assert_eq!((count.running, count.seen, count.aces_seen), (0.0, 0, 0));
+        // The burned cards were never seen, and aren't left to deal either
+        assert_eq!(count.shoe, 6 * 52 - 5);
+        assert_eq!(count.decks_remaining(), (6.0 * 52.0 - 5.0) / 52.0);
+    }
+
+    #[test]
+    fn the_hole_card_is_counted_once_revealed() {
+        let mut dealer = quiet_dealer(TableRules::default());
+        dealer.shuffle();
+        dealer.dealer_card().unwrap();
+        dealer.deal_hole_card().unwrap();
+        let hole = dealer.hand.cards.as_ref().unwrap()[1];
+        let before = dealer.counts[0].clone();
+        assert_eq!(before.seen, 1);
+        dealer.reveal_hole_card();
+        dealer.reveal_hole_card();
+        let after = &dealer.counts[0];
+        assert_eq!(after.seen, 2);
+        assert_eq!(after.running, before.running + after.system.tag(hole));
+    }
+
+    #[test]
+    fn a_continuous_shuffler_starts_the_count_over_every_round() {
+        let rules = TableRules {
+            shoe_mode: ShoeMode::Continuous,
+            ..TableRules::default()
+        };
+        let mut game = Game::new(rules, 3).unwrap();
+        game.dealer.ui = Ui::new(Box::new(ScriptedInput::default()), Box::new(SilentOutput));
+        game.dealer.shuffle();
+        let mut player = Player::new(String::from("Bot"));
+        player.strategy = Box::new(ChartStrategy::new(
+            game.dealer.strategy.clone(),
+            Box::new(FlatBet(Money::from_dollars(5))),
+        ));
+        game.players = Some(vec![player]);
+        game.state = GameState::RoundStart;
+        for round in 1..=3 {
+            while game.state != GameState::RoundEnd {
+                game.step();
+            }
+            assert!(game.dealer.counts[0].seen >= 3);
+            game.step();
+            assert_eq!(game.round_number, round);
+            let count = &game.dealer.counts[0];
+            assert_eq!((count.running, count.seen), (0.0, 0));
+            assert_eq!(count.shoe, game.dealer.shoe.remaining());
+        }
+    }
+}
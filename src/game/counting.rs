@@ -0,0 +1,323 @@
+use crate::game::card::Card;
+
+use std::fmt;
+use std::path::Path;
+
+/**
+ * Names of the counting systems `CountingSystem::from_name` knows
+ */
+pub const SYSTEMS: [&str; 7] = [
+    "hi-lo",
+    "ko",
+    "hi-opt-1",
+    "hi-opt-2",
+    "omega-2",
+    "zen",
+    "wong-halves",
+];
+
+/**
+ * Ranks as tag tables list them, Ace first and every ten-value card as 10
+ */
+const RANKS: [&str; 10] = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10"];
+
+/**
+ * Why a counting system couldn't be loaded
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum CountError {
+    Io(String),
+    // The file isn't valid TOML
+    Syntax(String),
+    UnknownRank(String),
+    BadTag(String),
+    MissingTags(Vec<&'static str>),
+}
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CountError::Io(error) => write!(f, "couldn't read the count: {}", error),
+            CountError::Syntax(error) => write!(f, "{}", error),
+            CountError::UnknownRank(rank) => {
+                write!(f, "unknown rank \"{}\", expected A, 2 to 9 or 10", rank)
+            }
+            CountError::BadTag(rank) => write!(f, "the tag for {} should be a number", rank),
+            CountError::MissingTags(ranks) => write!(f, "missing tags for {}", ranks.join(", ")),
+        }
+    }
+}
+impl std::error::Error for CountError {}
+
+/**
+ * A card counting system: what each rank adds to the running count, the
+ * count to start a shoe on, and whether Aces are also counted on the side
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountingSystem {
+    pub name: String,
+    // Tag for each rank, Ace first and ten-value cards last
+    pub tags: [f64; 10],
+    // Initial running count for every deck past the first. Unbalanced
+    // systems start below zero so the count ends the shoe at their pivot
+    pub irc_per_deck: f64,
+    // Track the Aces left, for systems whose tags leave them out
+    pub ace_side_count: bool,
+}
+impl CountingSystem {
+    pub fn new(name: &str, tags: [f64; 10]) -> Self {
+        CountingSystem {
+            name: String::from(name),
+            tags,
+            irc_per_deck: 0.0,
+            ace_side_count: false,
+        }
+    }
+    pub fn hi_lo() -> Self {
+        CountingSystem::new(
+            "Hi-Lo",
+            [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, -1.0],
+        )
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut system = match name.to_lowercase().as_str() {
+            "hi-lo" => return Some(CountingSystem::hi_lo()),
+            "ko" => CountingSystem::new("KO", [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, -1.0]),
+            "hi-opt-1" => CountingSystem::new(
+                "Hi-Opt I",
+                [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, -1.0],
+            ),
+            "hi-opt-2" => CountingSystem::new(
+                "Hi-Opt II",
+                [0.0, 1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 0.0, 0.0, -2.0],
+            ),
+            "omega-2" => CountingSystem::new(
+                "Omega II",
+                [0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 0.0, -1.0, -2.0],
+            ),
+            "zen" => {
+                CountingSystem::new("Zen", [-1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 0.0, 0.0, -2.0])
+            }
+            "wong-halves" => CountingSystem::new(
+                "Wong Halves",
+                [-1.0, 0.5, 1.0, 1.0, 1.5, 1.0, 0.5, 0.0, -0.5, -1.0],
+            ),
+            _ => return None,
+        };
+        // Ace-neutral systems are played with an Ace side count
+        system.ace_side_count = system.tag_of(1) == 0.0;
+        system.irc_per_deck = system.pivot_irc_per_deck();
+        Some(system)
+    }
+    /**
+     * Load a tag table from a TOML file, e.g.
+     *
+     * name = "Red Seven"
+     * [tags]
+     * A = -1
+     * 2 = 1
+     * ...
+     * 10 = -1
+     *
+     * `irc_per_deck` and `ace_side_count` are optional, and default the
+     * same way as the built-in systems: the pivot starting count, and an
+     * Ace side count when Aces are tagged 0
+     */
+    pub fn load(path: &Path) -> Result<Self, CountError> {
+        let text = std::fs::read_to_string(path).map_err(|e| CountError::Io(e.to_string()))?;
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Custom");
+        CountingSystem::from_toml(&text, name)
+    }
+    pub fn from_toml(text: &str, default_name: &str) -> Result<Self, CountError> {
+        let table = text
+            .parse::<toml::Table>()
+            .map_err(|e| CountError::Syntax(e.to_string()))?;
+        let name = table
+            .get("name")
+            .and_then(|name| name.as_str())
+            .unwrap_or(default_name);
+        let given = table
+            .get("tags")
+            .and_then(|tags| tags.as_table())
+            .ok_or_else(|| CountError::Syntax(String::from("expected a [tags] table")))?;
+        let mut tags = [None; 10];
+        for (rank, tag) in given {
+            let index = rank_index(rank).ok_or_else(|| CountError::UnknownRank(rank.clone()))?;
+            let tag = tag
+                .as_float()
+                .or_else(|| tag.as_integer().map(|n| n as f64))
+                .ok_or_else(|| CountError::BadTag(rank.clone()))?;
+            tags[index] = Some(tag);
+        }
+        let missing: Vec<&'static str> = RANKS
+            .iter()
+            .zip(tags)
+            .filter(|(_, tag)| tag.is_none())
+            .map(|(rank, _)| *rank)
+            .collect();
+        if !missing.is_empty() {
+            return Err(CountError::MissingTags(missing));
+        }
+        let mut system = CountingSystem::new(name, tags.map(Option::unwrap_or_default));
+        system.irc_per_deck = match table.get("irc_per_deck") {
+            Some(irc) => irc
+                .as_float()
+                .or_else(|| irc.as_integer().map(|n| n as f64))
+                .ok_or_else(|| {
+                    CountError::Syntax(String::from("irc_per_deck should be a number"))
+                })?,
+            None => system.pivot_irc_per_deck(),
+        };
+        system.ace_side_count = match table.get("ace_side_count") {
+            Some(side) => side.as_bool().ok_or_else(|| {
+                CountError::Syntax(String::from("ace_side_count should be true or false"))
+            })?,
+            None => system.tag_of(1) == 0.0,
+        };
+        Ok(system)
+    }
+    /**
+     * Tag for a card value, 1 for an Ace to 10
+     */
+    pub fn tag_of(&self, value: u8) -> f64 {
+        self.tags[usize::from(value.clamp(1, 10)) - 1]
+    }
+    pub fn tag(&self, card: Card) -> f64 {
+        self.tag_of(card.value())
+    }
+    /**
+     * The count after a whole deck, four of each rank and sixteen tens
+     */
+    pub fn deck_total(&self) -> f64 {
+        self.tags[..9].iter().sum::<f64>() * 4.0 + self.tags[9] * 16.0
+    }
+    /**
+     * Balanced systems count back to zero over a deck, which makes their
+     * true count meaningful
+     */
+    pub fn balanced(&self) -> bool {
+        self.deck_total() == 0.0
+    }
+    /**
+     * Initial running count per deck that has an unbalanced count end the
+     * shoe on its pivot, the count of a single deck (+4 for KO), whatever
+     * the number of decks. Nothing for a balanced one
+     */
+    pub fn pivot_irc_per_deck(&self) -> f64 {
+        if self.balanced() {
+            0.0
+        } else {
+            -self.deck_total()
+        }
+    }
+    /**
+     * Running count to start a shoe of this many decks on
+     */
+    pub fn irc(&self, decks: u8) -> f64 {
+        self.irc_per_deck * f64::from(decks.saturating_sub(1))
+    }
+}
+
+fn rank_index(rank: &str) -> Option<usize> {
+    match rank.trim().to_ascii_uppercase().as_str() {
+        "T" => Some(9),
+        rank => RANKS.iter().position(|r| *r == rank),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HI_OPT_1: &str = "[tags]
+A = 0
+2 = 0
+3 = 1
+4 = 1
+5 = 1
+6 = 1
+7 = 0
+8 = 0
+9 = 0
+10 = -1";
+
+    #[test]
+    fn built_in_systems_tag_cards_as_published() {
+        let tags = |name: &str| CountingSystem::from_name(name).unwrap().tags;
+        assert_eq!(
+            tags("hi-lo"),
+            [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, -1.0]
+        );
+        assert_eq!(tags("ko")[6], 1.0);
+        assert_eq!(
+            tags("omega-2"),
+            [0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 0.0, -1.0, -2.0]
+        );
+        assert_eq!(
+            tags("wong-halves"),
+            [-1.0, 0.5, 1.0, 1.0, 1.5, 1.0, 0.5, 0.0, -0.5, -1.0]
+        );
+        for name in SYSTEMS {
+            let system = CountingSystem::from_name(name).unwrap();
+            // Only KO is unbalanced, and only the Ace-neutral systems
+            // keep an Ace side count
+            assert_eq!(system.balanced(), name != "ko", "{}", name);
+            let ace_neutral = ["hi-opt-1", "hi-opt-2", "omega-2"].contains(&name);
+            assert_eq!(system.ace_side_count, ace_neutral, "{}", name);
+        }
+        assert_eq!(CountingSystem::from_name("HI-LO").unwrap().name, "Hi-Lo");
+        assert_eq!(CountingSystem::from_name("red-seven"), None);
+    }
+
+    #[test]
+    fn ko_starts_a_six_deck_shoe_at_minus_20() {
+        let ko = CountingSystem::from_name("ko").unwrap();
+        assert_eq!(ko.deck_total(), 4.0);
+        assert_eq!(ko.irc(6), -20.0);
+        assert_eq!(ko.irc(1), 0.0);
+        assert_eq!(CountingSystem::hi_lo().irc(6), 0.0);
+    }
+
+    #[test]
+    fn wong_halves_is_balanced_with_half_point_tags() {
+        let halves = CountingSystem::from_name("wong-halves").unwrap();
+        assert_eq!(halves.deck_total(), 0.0);
+        assert!(halves.balanced());
+        assert_eq!(halves.irc(8), 0.0);
+    }
+
+    #[test]
+    fn loads_a_tag_table_with_the_built_in_defaults() {
+        let mut built_in = CountingSystem::from_name("hi-opt-1").unwrap();
+        built_in.name = String::from("Mine");
+        assert_eq!(
+            CountingSystem::from_toml(HI_OPT_1, "Mine").unwrap(),
+            built_in
+        );
+        let named = format!("name = \"Hi-Opt I\"\nace_side_count = false\n{}", HI_OPT_1);
+        let system = CountingSystem::from_toml(&named, "Mine").unwrap();
+        assert_eq!(system.name, "Hi-Opt I");
+        assert!(!system.ace_side_count);
+    }
+
+    #[test]
+    fn reports_what_is_wrong_with_a_tag_table() {
+        let missing = HI_OPT_1.replace("5 = 1\n", "").replace("\n10 = -1", "");
+        assert_eq!(
+            CountingSystem::from_toml(&missing, "Mine"),
+            Err(CountError::MissingTags(vec!["5", "10"]))
+        );
+        let unknown = HI_OPT_1.replace("A = 0", "Q = 0");
+        assert_eq!(
+            CountingSystem::from_toml(&unknown, "Mine"),
+            Err(CountError::UnknownRank(String::from("Q")))
+        );
+        let bad = HI_OPT_1.replace("7 = 0", "7 = \"zero\"");
+        assert_eq!(
+            CountingSystem::from_toml(&bad, "Mine"),
+            Err(CountError::BadTag(String::from("7")))
+        );
+    }
+}
@@ -1,23 +1,67 @@
+use crate::game::betting::BetSituation;
 use crate::game::card::Card;
-use crate::game::deck::Deck;
-use crate::game::hand::{Hand, HandState};
+use crate::game::count::Count;
+use crate::game::counting::CountingSystem;
+use crate::game::hand::{Hand, HandState, HandValue};
+use crate::game::ledger::{Ledger, TransactionKind};
+use crate::game::money::Money;
 use crate::game::player::Player;
-
-use rand::Rng;
-use std::collections::HashMap;
-use std::io;
+use crate::game::render::Renderer;
+use crate::game::rules::{DoubleRule, HoleCardRule, SurrenderRule, TableRules};
+use crate::game::shoe::{Shoe, ShoeError};
+use crate::game::strategy::{Action, BasicStrategy, Situation, Strategy};
+use crate::game::ui::{SeatView, Ui};
 
 #[derive(Debug)]
 pub struct Dealer {
-    pub decks: Option<Vec<Deck>>,
+    pub shoe: Shoe,
     pub hand: Hand,
+    pub rules: TableRules,
+    pub hole_card_hidden: bool,
+    pub ledger: Ledger,
+    // The cards everyone at the table has seen since the shuffle, counted
+    // with each system side by side
+    pub counts: Vec<Count>,
+    // Announce the counts each round and show them on the table
+    pub show_count: bool,
+    // Basic strategy for these rules, the chart computer players are
+    // seated with
+    pub strategy: BasicStrategy,
+    pub ui: Ui,
 }
 impl Dealer {
-    pub fn new() -> Self {
-        Dealer {
-            decks: Some(Vec::<Deck>::new()),
+    pub fn new(rules: TableRules, seed: u64) -> Result<Self, ShoeError> {
+        Ok(Dealer {
+            shoe: Shoe::new(
+                rules.decks,
+                rules.shoe_mode,
+                rules.penetration,
+                rules.burn_cards,
+                seed,
+            )?,
             hand: Hand::new(),
-        }
+            strategy: BasicStrategy::new(&rules),
+            counts: vec![Count::new(CountingSystem::hi_lo(), rules.decks)],
+            rules,
+            hole_card_hidden: false,
+            ledger: Ledger::new(),
+            show_count: false,
+            ui: Ui::terminal(Renderer::detect()),
+        })
+    }
+    /**
+     * Move chips between a player and the house, keeping the books
+     */
+    pub fn transfer(&mut self, player: &mut Player, kind: TransactionKind, amount: Money) {
+        player.set_pay(amount);
+        self.ledger.record(&player.name, kind, amount);
+    }
+    /**
+     * Take a player's bet for the round out of their bankroll
+     */
+    pub fn place_wager(&mut self, player: &mut Player, wager: Money) {
+        player.set_wager(wager);
+        self.transfer(player, TransactionKind::Wager, -wager);
     }
     /**
      * Remove a player from the game
@@ -26,285 +70,685 @@ impl Dealer {
         player.active = false;
     }
     pub fn hand_status(&mut self, player: &mut Player) {
-        if player.hand.state == HandState::Blackjack || player.hand.state == HandState::Lose {
-            return;
-        }
-        let player_total = player.hand.get_total_single();
-        let dealer_total = self.hand.get_total_single();
-        if dealer_total > 21 && player_total < 22 {
-            player.hand.state = HandState::Win;
-            return;
-        }
-        if player_total > dealer_total {
-            player.hand.state = HandState::Win;
-        } else if player_total == dealer_total {
-            player.hand.state = HandState::Push;
-        } else {
-            player.hand.state = HandState::Lose;
-        }
-    }
-    pub fn payout(&mut self, player: &mut Player) {
-        println!("\n");
-        match player.hand.state {
-            HandState::Idle => {}
-            HandState::Push => {
-                println!("{}, push!", player.name);
+        let dealer = self.hand.value();
+        let dealer_total = dealer.total;
+        let dealer_blackjack = dealer.blackjack;
+        player.hands.iter_mut().for_each(|hand| {
+            // A dealer blackjack beats everything but another blackjack,
+            // including any doubles and splits made along the way
+            if dealer_blackjack {
+                hand.state = match hand.state {
+                    HandState::Blackjack => HandState::Push,
+                    HandState::EvenMoney => HandState::EvenMoney,
+                    // Only early surrender escapes a dealer blackjack
+                    HandState::Surrender if self.rules.surrender_rule == SurrenderRule::Early => {
+                        HandState::Surrender
+                    }
+                    _ => HandState::Lose,
+                };
+                return;
             }
-            HandState::Win => {
-                println!("{}, you win ${}", player.name, player.wager * 2);
-                player.set_pay(player.wager * 2);
+            let settled = [
+                HandState::Blackjack,
+                HandState::Lose,
+                HandState::Surrender,
+                HandState::EvenMoney,
+            ];
+            if settled.contains(&hand.state) {
+                return;
             }
-            HandState::Lose => {
-                println!("{}, you lose ${}", player.name, player.wager);
-                player.set_pay(-player.wager);
+            let player_total = hand.value().total;
+            if dealer_total > 21 && player_total < 22 {
+                hand.state = HandState::Win;
+                return;
             }
-            HandState::Blackjack => {
-                println!("{}, you win ${}", player.name, player.wager * 3);
-                player.set_pay(player.wager * 3);
+            if player_total > dealer_total {
+                hand.state = HandState::Win;
+            } else if player_total == dealer_total {
+                hand.state = HandState::Push;
+            } else {
+                hand.state = HandState::Lose;
+            }
+        });
+    }
+    pub fn payout(&mut self, player: &mut Player) {
+        self.ui.say("\n");
+        for i in 0..player.hands.len() {
+            let name = player.hand_name(i);
+            let hand = &player.hands[i];
+            let (wager, doubled) = (hand.wager, hand.doubled);
+            match hand.state {
+                HandState::Idle => {}
+                HandState::Push => {
+                    self.ui.say(&format!("{}, push!", name));
+                    self.transfer(player, TransactionKind::Payout, wager);
+                }
+                HandState::Win => {
+                    if doubled {
+                        self.ui.say(&format!("{}, your double down wins!", name));
+                    }
+                    self.ui.say(&format!("{}, you win {}", name, wager));
+                    self.transfer(player, TransactionKind::Payout, wager * 2);
+                }
+                HandState::Lose => {
+                    if doubled {
+                        self.ui.say(&format!("{}, your double down loses!", name));
+                    }
+                    // The stake was taken when the bet was placed
+                    self.ui.say(&format!("{}, you lose {}", name, wager));
+                }
+                HandState::Blackjack => {
+                    let (numerator, denominator) = self.rules.blackjack_pays;
+                    let winnings = wager.ratio(numerator, denominator, self.rules.rounding);
+                    self.ui.say(&format!("{}, you win {}", name, winnings));
+                    self.transfer(player, TransactionKind::Payout, wager + winnings);
+                }
+                HandState::EvenMoney => {
+                    // Paid when it was taken
+                    self.ui.say(&format!("{}, even money paid {}", name, wager));
+                }
+                HandState::Surrender => {
+                    // Half the stake is returned
+                    let refund = wager.ratio(1, 2, self.rules.rounding);
+                    self.ui.say(&format!("{}, you surrender {}", name, wager - refund));
+                    self.transfer(player, TransactionKind::Surrender, refund);
+                }
             }
         }
-        player.clear_wager();
-        player.hand.state = HandState::Idle;
         self.hand.state = HandState::Idle;
     }
     /**
-     * As player for wager
+     * Offer insurance, or even money to a player holding blackjack. False
+     * while the player's answer is still to come
      */
-    pub fn ask_wager(&mut self, player: &mut Player) {
-        loop {
-            println!("{}, how much would you like to wager?", player.name);
-            println!("Current bankroll: ${}", player.bankroll);
-            let mut response = String::new();
-            io::stdin()
-                .read_line(&mut response)
-                .expect("couldn't read line");
-            let number = response.trim().parse::<i64>();
-            if number.is_ok() {
-                let wager = number.unwrap();
-                if wager == 0 {
-                    println!("Wager must be greater than 0")
-                } else if wager > player.bankroll {
-                    println!("You don't have that much to wager")
-                } else {
-                    player.set_wager(wager);
-                    break;
-                }
-            } else {
-                println!("Not a number, please try again");
-            }
+    pub fn offer_insurance(&mut self, player: &mut Player) -> bool {
+        if player.hands[0].state == HandState::Surrender {
+            return true;
         }
+        if player.hands[0].value().blackjack {
+            return self.offer_even_money(player);
+        }
+        let wager = player.hands[0].wager;
+        let max = wager.ratio(1, 2, self.rules.rounding).min(player.bankroll);
+        if max <= Money::ZERO {
+            return true;
+        }
+        let amount = self
+            .with_situation(player, 0, false, false, false, |strategy, situation, ui| {
+                strategy.insurance(situation, max, ui)
+            })
+            .clamp(Money::ZERO, max);
+        if self.ui.waiting.is_some() {
+            return false;
+        }
+        if amount > Money::ZERO {
+            self.ui.say(&format!("{} takes {} insurance", player.name, amount));
+        }
+        player.insurance = amount;
+        self.transfer(player, TransactionKind::Insurance, -amount);
+        true
     }
     /**
-     * Ask for the number of players
+     * Offer a blackjack 1:1 now instead of the blackjack payout once the
+     * dealer's hand is known. The hand is settled on the spot
      */
-    pub fn human_or_computer(&mut self, player: &mut Player) {
-        loop {
-            println!("{}: are you Human (h) or Computer (c)?", player.name);
-            let mut mode: String = String::new();
-            // Get user input
-            std::io::stdin()
-                .read_line(&mut mode)
-                .expect("unable to read line");
-            // The only options are c or h
-            if !["h", "c"].contains(&mode.as_str().to_lowercase().trim()) {
-                continue;
-            }
-            // Return if the player is human based on input
-            if mode.contains("h") {
-                player.set_human(true);
-                break;
-            } else {
-                player.set_human(false);
-                break;
-            }
+    pub fn offer_even_money(&mut self, player: &mut Player) -> bool {
+        let even_money =
+            self.with_situation(player, 0, false, false, false, |strategy, situation, ui| {
+                strategy.even_money(situation, ui)
+            });
+        if self.ui.waiting.is_some() {
+            return false;
+        }
+        if even_money {
+            self.ui.say(&format!("{} takes even money", player.name));
+            let wager = player.hands[0].wager;
+            player.hands[0].state = HandState::EvenMoney;
+            self.transfer(player, TransactionKind::Payout, wager * 2);
         }
+        true
     }
-    pub fn number_of_players(&self) -> u8 {
-        println!("How many players are playing?");
+    /**
+     * Offer early surrender before the dealer checks for blackjack. False
+     * while the player's answer is still to come
+     */
+    pub fn offer_early_surrender(&mut self, player: &mut Player) -> bool {
+        if !self.can_surrender(player, 0) {
+            return true;
+        }
+        let surrender =
+            self.with_situation(player, 0, false, false, true, |strategy, situation, ui| {
+                strategy.surrender(situation, ui)
+            });
+        if self.ui.waiting.is_some() {
+            return false;
+        }
+        if surrender {
+            self.ui.say(&format!("{} surrenders\n", player.name));
+            player.hands[0].state = HandState::Surrender;
+        }
+        true
+    }
+    /**
+     * Let a player's strategy decide on one of their hands, given what it
+     * can see of the table. The strategy and the `Ui` are handed to
+     * `decide` alongside the situation, as the situation borrows the rest
+     * of the player and the dealer
+     */
+    fn with_situation<T>(
+        &mut self,
+        player: &mut Player,
+        index: usize,
+        can_double: bool,
+        can_split: bool,
+        can_surrender: bool,
+        decide: impl FnOnce(&mut dyn Strategy, &Situation, &mut Ui) -> T,
+    ) -> T {
+        let situation = Situation {
+            name: &player.name,
+            hand: &player.hands[index],
+            upcard: self.upcard(),
+            bankroll: player.bankroll,
+            rules: &self.rules,
+            counts: &self.counts,
+            can_double,
+            can_split,
+            can_surrender,
+        };
+        decide(player.strategy.as_mut(), &situation, &mut self.ui)
+    }
+    /**
+     * Settle a player's insurance once the dealer's hole card is known.
+     * Insurance pays 2:1 and is independent of the player's hands
+     */
+    pub fn settle_insurance(&mut self, player: &mut Player) {
+        if player.insurance == Money::ZERO {
+            return;
+        }
+        let insurance = player.insurance;
+        if self.hand.value().blackjack {
+            self.ui.say(&format!("{}, insurance pays {}", player.name, insurance * 2));
+            self.transfer(player, TransactionKind::InsurancePayout, insurance * 3);
+        } else {
+            self.ui.say(&format!("{}, you lose {} insurance", player.name, insurance));
+        }
+        player.insurance = Money::ZERO;
+    }
+    /**
+     * Ask for the player's bet, kept within the table limits and their
+     * bankroll. None if they haven't answered, because the input ran out
+     * or the answer is still to come
+     */
+    pub fn ask_wager(&mut self, player: &mut Player) -> Option<Money> {
+        let bet = BetSituation {
+            name: &player.name,
+            bankroll: player.bankroll,
+            rules: &self.rules,
+            counts: &self.counts,
+        };
+        let wager = player.strategy.wager(&bet, &mut self.ui);
+        if self.ui.closed || self.ui.waiting.is_some() {
+            return None;
+        }
+        Some(
+            wager
+                .clamp(self.rules.min_bet, self.rules.max_bet)
+                .min(player.bankroll),
+        )
+    }
+    /**
+     * Ask whether a seat is played by a person or the computer, None
+     * without an answer
+     */
+    pub fn is_human(&mut self, player: &Player) -> Option<bool> {
+        let prompt = format!("{}: are you", player.name);
+        let mode = self.ui.choose(&prompt, &[('h', "Human"), ('c', "Computer")])?;
+        Some(mode == 'h')
+    }
+    /**
+     * Ask for the number of players, None without an answer
+     */
+    pub fn number_of_players(&mut self) -> Option<u8> {
         loop {
-            let mut response = String::new();
-            io::stdin()
-                .read_line(&mut response)
-                .expect("couldn't read line");
+            let response = self.ui.ask("How many players are playing?")?;
             let number = response.trim().parse::<u8>();
-            if number.is_ok() {
-                let number = number.unwrap();
+            if let Ok(number) = number {
                 if number == 0 {
-                    println!("Number of players must be greater than 0")
-                } else if number > 8 {
-                    println!("Number of players must be 8 or less")
+                    self.ui.say("Number of players must be greater than 0")
+                } else if number > self.rules.seats {
+                    self.ui.say(&format!("Number of players must be {} or less", self.rules.seats))
                 } else {
-                    return number;
+                    return Some(number);
                 }
             } else {
-                println!("Not a number, please try again");
+                self.ui.say("Not a number, please try again");
             }
         }
     }
-    pub fn dealer_turn(&mut self) {
+    /**
+     * The dealer's first card, the only one visible while players act
+     */
+    pub fn upcard(&self) -> Card {
+        self.hand
+            .cards
+            .iter()
+            .flatten()
+            .next()
+            .copied()
+            .expect("dealer has no upcard")
+    }
+    /**
+     * The dealer's cards as the table sees them, without a hidden hole card
+     */
+    pub fn visible_cards(&self) -> Vec<Card> {
+        let mut cards = self.hand.cards.clone().unwrap_or_default();
+        if self.hole_card_hidden && cards.len() > 1 {
+            cards.remove(1);
+        }
+        cards
+    }
+    /**
+     * Show the dealer's hand, keeping the hole card face down until revealed
+     */
+    pub fn display_hand(&mut self) {
+        let cards = self.hand.cards.clone().unwrap_or_default();
+        let hidden = self.hole_card_hidden;
+        if hidden {
+            let total = HandValue::from_cards(&[self.upcard()], false).total;
+            self.ui.show_hand("Dealer", &cards, 1, &total.to_string());
+        } else {
+            self.ui.show_hand("Dealer", &cards, 0, &self.hand.total_text());
+        }
+        let visible = self.visible_cards();
+        self.ui.update(|table| {
+            table.dealer = visible;
+            table.hole_card_hidden = hidden;
+        });
+    }
+    /**
+     * Peek at the hole card for blackjack when showing an Ace or ten-value
+     */
+    pub fn peek(&mut self) -> bool {
+        if self.rules.hole_card_rule != HoleCardRule::American || !self.hole_card_hidden {
+            return false;
+        }
+        let upcard = self.upcard();
+        if !upcard.is_ace() && upcard.value() != 10 {
+            return false;
+        }
+        self.ui.say("Dealer checks for blackjack...\n");
+        if self.hand.value().blackjack {
+            self.reveal_hole_card();
+            self.display_hand();
+            self.ui.say("Dealer blackjack!\n");
+            return true;
+        }
+        self.ui.say("No dealer blackjack\n");
+        false
+    }
+    /**
+     * Settle a player against a peeked dealer blackjack: naturals push and
+     * everything else loses the original bet
+     */
+    pub fn settle_dealer_blackjack(&mut self, player: &mut Player) {
+        player.hands.iter_mut().for_each(|hand| {
+            if [HandState::Surrender, HandState::EvenMoney].contains(&hand.state) {
+                return;
+            }
+            if hand.value().blackjack {
+                hand.state = HandState::Push;
+            } else {
+                hand.state = HandState::Lose;
+            }
+        });
+    }
+    pub fn dealer_turn(&mut self) -> Result<(), ShoeError> {
+        if self.hole_card_hidden {
+            self.ui.say("Dealer reveals the hole card");
+            self.reveal_hole_card();
+        }
         loop {
-            let total = self.hand.get_total_single();
-            println!("\nDealer hand:");
-            self.hand.display();
-            self.hand.display_total();
-            if total < 17 {
-                self.dealer_card();
-            } else if total > 21 {
-                println!("Dealer bust\n");
+            let value = self.hand.value();
+            self.display_hand();
+            let soft_17 = value.total == 17 && value.soft;
+            if value.total < 17 || (soft_17 && self.rules.dealer_hits_soft_17) {
+                self.dealer_card()?;
+            } else if value.bust {
+                self.ui.say("Dealer bust\n");
                 break;
-            } else if total == 21 && self.hand.count() == 2 {
-                println!("Dealer blackjack!\n");
+            } else if value.blackjack {
+                self.ui.say("Dealer blackjack!\n");
                 break;
             } else {
-                println!("Dealer stand\n");
+                self.ui.say("Dealer stand\n");
                 // stand
                 break;
             }
         }
+        Ok(())
     }
-    pub fn player_turn(&mut self, player: &mut Player) {
-        println!("{}, it is your turn:", player.name);
+    /**
+     * Play the player's hands from `hand`, the one reached so far or None
+     * before their turn starts. False while an answer is still to come,
+     * with `hand` left on the hand waiting for it
+     */
+    pub fn player_turn(
+        &mut self,
+        player: &mut Player,
+        hand: &mut Option<usize>,
+    ) -> Result<bool, ShoeError> {
+        if hand.is_none() {
+            self.ui.say(&format!("{}, it is your turn:", player.name));
+        }
+        // Splitting adds hands while we play, so index rather than iterate
+        let mut i = hand.unwrap_or(0);
+        while i < player.hands.len() {
+            *hand = Some(i);
+            if !self.play_hand(player, i)? {
+                return Ok(false);
+            }
+            i += 1;
+        }
+        *hand = None;
+        Ok(true)
+    }
+    /**
+     * Play out a single hand of the player. False while an answer is still
+     * to come, and playing the hand again picks up where it stopped
+     */
+    pub fn play_hand(&mut self, player: &mut Player, index: usize) -> Result<bool, ShoeError> {
+        if [HandState::Surrender, HandState::EvenMoney].contains(&player.hands[index].state) {
+            return Ok(true);
+        }
+        // A split hand receives its second card when it is played
+        if player.hands[index].count() == 1 {
+            self.deal_card(&mut player.hands[index])?;
+        }
         loop {
-            println!("\nDealer hand:");
-            self.hand.display();
-            self.hand.display_total();
-            println!("\n{} hand:", player.name);
-            player.hand.display();
-            player.hand.display_total();
-            let total = player.hand.get_total_single();
-            if total > 21 {
-                println!("{} bust\n", player.name);
-                player.hand.state = HandState::Lose;
+            let name = player.hand_name(index);
+            self.display_hand();
+            let hand = &player.hands[index];
+            let cards = hand.cards.as_deref().unwrap_or_default();
+            self.ui.show_hand(&name, cards, 0, &hand.total_text());
+            self.ui.update(|table| table.set_seat(SeatView::new(player, Some(index))));
+            let hand = &mut player.hands[index];
+            let value = hand.value();
+            if value.bust {
+                self.ui.say(&format!("{} bust\n", name));
+                hand.state = HandState::Lose;
                 break;
-            } else if total == 21 && player.hand.count() == 2 {
-                println!("{} blackjack!\n", player.name);
-                player.hand.state = HandState::Blackjack;
+            } else if value.blackjack {
+                self.ui.say(&format!("{} blackjack!\n", name));
+                hand.state = HandState::Blackjack;
                 break;
-            }
-            println!("\n");
-            let action = if player.human {
-                player.human_action()
-            } else {
-                player.computer_action()
-            };
-            if action.trim().to_lowercase() == "h" {
-                println!("{} hit\n", player.name);
-                self.deal_card(player);
-            } else if action.trim().to_lowercase() == "s" {
-                println!("{} stand\n", player.name);
+            } else if hand.doubled || hand.is_split_aces() {
+                // Doubled hands and split aces receive exactly one card
                 break;
             }
+            self.ui.say("\n");
+            let can_double = self.can_double(player, index);
+            let can_split = self.can_split(player, index);
+            let can_surrender =
+                self.rules.surrender_rule == SurrenderRule::Late && self.can_surrender(player, index);
+            // Strategies can read the table view, which must not give the
+            // hole card away
+            debug_assert_eq!(self.ui.table.dealer.len(), 1);
+            let action = self.with_situation(
+                player,
+                index,
+                can_double,
+                can_split,
+                can_surrender,
+                |strategy, situation, ui| strategy.action(situation, ui),
+            );
+            if self.ui.waiting.is_some() {
+                return Ok(false);
+            }
+            match action {
+                Action::Hit => {
+                    self.ui.say(&format!("{} hit\n", name));
+                    self.deal_card(&mut player.hands[index])?;
+                }
+                Action::Stand => {
+                    self.ui.say(&format!("{} stand\n", name));
+                    break;
+                }
+                Action::Double if can_double => {
+                    self.ui.say(&format!("{} double down\n", name));
+                    let wager = player.hands[index].wager;
+                    self.transfer(player, TransactionKind::Double, -wager);
+                    let hand = &mut player.hands[index];
+                    hand.wager += wager;
+                    hand.doubled = true;
+                    self.deal_card(hand)?;
+                }
+                Action::Split if can_split => {
+                    self.ui.say(&format!("{} split\n", name));
+                    self.split_hand(player, index)?;
+                }
+                Action::Surrender if can_surrender => {
+                    self.ui.say(&format!("{} surrenders\n", name));
+                    player.hands[index].state = HandState::Surrender;
+                    break;
+                }
+                // A strategy asking for something the table doesn't allow
+                // is asked again
+                _ => {}
+            }
         }
+        Ok(true)
     }
     /**
-     * Deal a card from the deck to the dealer
+     * Split a pair into two hands, each carrying the original wager
      */
-    pub fn dealer_card(&mut self) {
-        // Attempt to draw a card
-        if let Some(card) = self.draw_card() {
-            self.hand.cards.as_mut().unwrap().push(card);
-        } else {
-            // Deck is None -- time to shuffle
-            self.shuffle_decks();
-            self.dealer_card();
+    pub fn split_hand(&mut self, player: &mut Player, index: usize) -> Result<(), ShoeError> {
+        let hand = &mut player.hands[index];
+        let card = hand.cards.as_mut().unwrap().pop().unwrap();
+        hand.split = true;
+        let mut new_hand = Hand::new();
+        new_hand.wager = hand.wager;
+        new_hand.split = true;
+        new_hand.cards.as_mut().unwrap().push(card);
+        player.hands.insert(index + 1, new_hand);
+        let wager = player.hands[index].wager;
+        self.transfer(player, TransactionKind::Split, -wager);
+        self.deal_card(&mut player.hands[index])
+    }
+    /**
+     * Check if the player may double down on the given hand
+     */
+    pub fn can_double(&mut self, player: &mut Player, index: usize) -> bool {
+        // The bankroll must cover the extra stake
+        if player.hands[index].wager > player.bankroll {
+            return false;
+        }
+        let hand = &mut player.hands[index];
+        if hand.count() != 2 || hand.doubled {
+            return false;
+        }
+        if hand.split && !self.rules.double_after_split {
+            return false;
+        }
+        let total = hand.value().total;
+        match self.rules.double_rule {
+            DoubleRule::AnyTwo => true,
+            DoubleRule::NineToEleven => (9..=11).contains(&total),
+            DoubleRule::TenToEleven => (10..=11).contains(&total),
         }
     }
     /**
-     * Deal a card from the deck to a player
+     * Check if the player may surrender the given hand. Only an untouched
+     * starting hand that isn't a blackjack can be surrendered
      */
-    pub fn deal_card(&mut self, player: &mut Player) {
-        // Attempt to draw a card
-        if let Some(card) = self.draw_card() {
-            player.hand.cards.as_mut().unwrap().push(card);
-        } else {
-            // Deck is None -- time to shuffle
-            self.shuffle_decks();
-            self.deal_card(player);
+    pub fn can_surrender(&mut self, player: &mut Player, index: usize) -> bool {
+        if self.rules.surrender_rule == SurrenderRule::None || player.hands.len() > 1 {
+            return false;
         }
+        let hand = &mut player.hands[index];
+        hand.count() == 2 && !hand.value().blackjack && hand.state == HandState::Idle
     }
     /**
-     * Draw a card from the deck
+     * Check if the player may split the given hand
      */
-    pub fn draw_card(&mut self) -> Option<Card> {
-        let decks = self.decks.as_mut().unwrap();
-        let mut card: Option<Card> = None;
-        for i in 0..decks.len() {
-            // Draw a card from a non-empty deck
-            if decks[i].cards.as_ref().unwrap().len() > 0 {
-                card = decks[i].cards.as_mut().unwrap().pop();
-                break;
-            }
+    pub fn can_split(&mut self, player: &mut Player, index: usize) -> bool {
+        // The bankroll must cover the new hand's stake
+        if player.hands[index].wager > player.bankroll {
+            return false;
+        }
+        if player.hands.len() >= self.rules.max_hands {
+            return false;
         }
-        card
-    }
-    pub fn create_decks(&mut self) {
-        let number_of_decks: u8 = 6;
-        // Create new Vec<Deck>
-        let mut decks = Vec::new();
-        // Create n decks
-        for _i in 0..number_of_decks {
-            let deck = self.create_deck();
-            decks.push(deck);
-        }
-        // Assign the dealer some decks
-        self.decks = Some(decks);
-    }
-    pub fn create_deck(&mut self) -> Deck {
-        // Pips are not used, they are the symbols on the Card
-        // Suit are the symbols on the cards
-        // Face value hash map
-        let face_values = HashMap::from([
-            ("Ace", 1),
-            ("Two", 2),
-            ("Three", 3),
-            ("Four", 4),
-            ("Five", 5),
-            ("Six", 6),
-            ("Seven", 7),
-            ("Eight", 8),
-            ("Nine", 9),
-            ("Ten", 10),
-            ("Jack", 10),
-            ("Queen", 10),
-            ("King", 10),
-        ]);
-        // Empty card vector, this will be the card deck
-        let mut cards: Vec<Card> = Vec::new();
+        player.hands[index].is_pair(self.rules.split_by_value)
+    }
+    /**
+     * Shuffle the shoe and burn, telling the table
+     */
+    pub fn shuffle(&mut self) {
+        self.ui.say("Shuffling the shoe");
+        self.shoe.shuffle();
+        if self.shoe.burn > 0 {
+            self.ui.say(&format!("Burning {} card(s)\n", self.shoe.burn));
+        }
+        let shoe = self.shoe.remaining();
+        self.counts.iter_mut().for_each(|count| count.reset(shoe));
+    }
+    /**
+     * Draw from the shoe, announcing the cut card when it comes out
+     */
+    fn draw(&mut self) -> Result<Card, ShoeError> {
+        if self.shoe.remaining() == 0 {
+            self.ui.say("The shoe is empty");
+            self.shuffle();
+        }
+        let cut_card_reached = self.shoe.cut_card_reached;
+        let card = self.shoe.draw()?;
+        if !cut_card_reached && self.shoe.cut_card_reached {
+            self.ui.say("The cut card is out, last round before the shuffle");
+        }
+        Ok(card)
+    }
+    /**
+     * Add a card everyone can see to every count
+     */
+    fn see(&mut self, card: Card) {
+        self.counts.iter_mut().for_each(|count| count.see(card));
+    }
+    /**
+     * Deal the dealer's face-down hole card, left out of the count until
+     * it is revealed
+     */
+    pub fn deal_hole_card(&mut self) -> Result<(), ShoeError> {
+        let card = self.draw()?;
+        self.hand.cards.as_mut().unwrap().push(card);
+        self.hole_card_hidden = true;
+        Ok(())
+    }
+    /**
+     * Turn the hole card over for everyone to see
+     */
+    pub fn reveal_hole_card(&mut self) {
+        if !self.hole_card_hidden {
+            return;
+        }
+        self.hole_card_hidden = false;
+        if let Some(card) = self.hand.cards.as_ref().and_then(|cards| cards.get(1)) {
+            let card = *card;
+            self.see(card);
+        }
+    }
+    /**
+     * Deal a card from the shoe to the dealer
+     */
+    pub fn dealer_card(&mut self) -> Result<(), ShoeError> {
+        let card = self.draw()?;
+        self.see(card);
+        self.hand.cards.as_mut().unwrap().push(card);
+        Ok(())
+    }
+    /**
+     * Deal a card from the shoe to a player's hand
+     */
+    pub fn deal_card(&mut self, hand: &mut Hand) -> Result<(), ShoeError> {
+        let card = self.draw()?;
+        self.see(card);
+        hand.cards.as_mut().unwrap().push(card);
+        Ok(())
+    }
+    /**
+     * Hand back everything a player put in or was paid this round, for a
+     * round that is called off
+     */
+    pub fn refund(&mut self, player: &mut Player) {
+        let amount = self.ledger.refund(&player.name);
+        player.set_pay(amount);
+        player.insurance = Money::ZERO;
+    }
+}
 
-        for suit in ["Hearts", "Diamonds", "Spades", "Clubs"] {
-            // for each face value
-            for fv in face_values.iter() {
-                let (face, value) = fv;
-                let card = Card {
-                    suit: String::from(suit),
-                    face: String::from(*face),
-                    value: *value,
-                };
-                cards.push(card);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::betting::BetSituation;
+    use crate::game::strategy::{Action, Strategy};
+    use crate::game::ui::{ScriptedInput, SilentOutput};
+
+    /**
+     * Always takes even money, and stands otherwise
+     */
+    #[derive(Debug)]
+    struct EvenMoney;
+    impl Strategy for EvenMoney {
+        fn wager(&mut self, bet: &BetSituation, _ui: &mut Ui) -> Money {
+            bet.rules.min_bet
+        }
+        fn even_money(&mut self, _situation: &Situation, _ui: &mut Ui) -> bool {
+            true
+        }
+        fn surrender(&mut self, _situation: &Situation, _ui: &mut Ui) -> bool {
+            false
         }
-        // Return a deck with some cards
-        Deck { cards: Some(cards) }
-    }
-    pub fn shuffle_decks(&mut self) {
-        self.create_decks();
-        // The dealer's decks
-        self.decks.iter_mut().flatten().for_each(|deck| {
-            // Unwrap the cards in the deck
-            let cards = deck.cards.as_mut().expect("cards are none");
-            let mut rng = rand::thread_rng();
-            let mut temp: Vec<Card> = Vec::new();
-            // Rearrange cards
-            while cards.len() > 0 {
-                let idx = rng.gen_range(0..=cards.len() - 1);
-                let card = cards.get(idx).expect("card index doesn't exist").clone();
-                temp.push(card);
-                cards.remove(idx);
+        fn action(&mut self, _situation: &Situation, _ui: &mut Ui) -> Action {
+            Action::Stand
+        }
+    }
+
+    /**
+     * A $5 natural against a dealer Ace, with the hole card given
+     */
+    fn natural_against_ace(blackjack_pays: (i64, i64), dealer_hand: &str) -> (Dealer, Player) {
+        let rules = TableRules {
+            blackjack_pays,
+            ..TableRules::default()
+        };
+        let mut dealer = Dealer::new(rules, 1).unwrap();
+        dealer.ui = Ui::new(Box::new(ScriptedInput::default()), Box::new(SilentOutput));
+        dealer.hand = dealer_hand.parse().unwrap();
+        dealer.hole_card_hidden = true;
+        let mut player = Player::new(String::from("Player 1"));
+        player.strategy = Box::new(EvenMoney);
+        dealer.place_wager(&mut player, Money::from_dollars(5));
+        player.hands[0].cards = "As Kd".parse::<Hand>().unwrap().cards;
+        (dealer, player)
+    }
+
+    #[test]
+    fn even_money_pays_one_to_one_whatever_blackjack_pays() {
+        for pays in [(3, 2), (6, 5)] {
+            for dealer_hand in ["Ah 9c", "Ah Kc"] {
+                let (mut dealer, mut player) = natural_against_ace(pays, dealer_hand);
+                dealer.offer_insurance(&mut player);
+                assert_eq!(player.hands[0].state, HandState::EvenMoney);
+                assert_eq!(player.insurance, Money::ZERO);
+                if dealer.peek() {
+                    dealer.settle_dealer_blackjack(&mut player);
+                } else {
+                    dealer.reveal_hole_card();
+                    dealer.hand_status(&mut player);
+                }
+                dealer.payout(&mut player);
+                assert_eq!(player.bankroll, Money::from_dollars(105));
+                assert_eq!(dealer.ledger.player_total("Player 1"), Money::from_dollars(5));
             }
-            // The shuffled deck
-            let shuffled_deck = Deck { cards: Some(temp) };
-            // Assign the dealer's deck to the shuffled deck
-            *deck = shuffled_deck;
-        });
+        }
     }
 }
@@ -1,7 +1,24 @@
-use crate::game::card::Card;
+use crate::game::card::{Card, Rank, Suit};
 
 #[derive(Debug)]
 pub struct Deck {
     pub cards: Option<Vec<Card>>,
 }
-impl Deck {}
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new()
+    }
+}
+impl Deck {
+    /**
+     * A fresh 52 card deck
+     */
+    pub fn new() -> Self {
+        let cards: Vec<Card> = Suit::ALL
+            .iter()
+            .flat_map(|suit| Rank::ALL.iter().map(|rank| Card::new(*rank, *suit)))
+            .collect();
+        // Return a deck with some cards
+        Deck { cards: Some(cards) }
+    }
+}
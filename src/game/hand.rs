@@ -1,9 +1,16 @@
-use crate::game::card::Card;
+use crate::game::card::{Card, ParseCardError};
+use crate::game::money::Money;
+
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq)]
 pub struct Hand {
     pub state: HandState,
     pub cards: Option<Vec<Card>>,
+    pub wager: Money,
+    pub doubled: bool,
+    pub split: bool,
 }
 #[derive(Debug, PartialEq)]
 pub enum HandState {
@@ -12,12 +19,53 @@ pub enum HandState {
     Lose,
     Push,
     Blackjack,
+    Surrender,
+    // A blackjack paid 1:1 before the dealer checks the hole card
+    EvenMoney,
+}
+/**
+ * The best total of a set of cards, and what kind of hand it makes
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandValue {
+    pub total: u8,
+    // An Ace is counted as 11
+    pub soft: bool,
+    pub bust: bool,
+    // 21 on the first two cards, which a split hand can never be
+    pub blackjack: bool,
+    // Two cards of the same rank
+    pub pair: bool,
+}
+impl HandValue {
+    pub fn from_cards(cards: &[Card], split: bool) -> Self {
+        let hard: u8 = cards.iter().map(|card| card.value()).sum();
+        let has_ace = cards.iter().any(|card| card.is_ace());
+        // At most one Ace can count as 11
+        let soft = has_ace && hard + 10 <= 21;
+        let total = if soft { hard + 10 } else { hard };
+        HandValue {
+            total,
+            soft,
+            bust: total > 21,
+            blackjack: total == 21 && cards.len() == 2 && !split,
+            pair: matches!(cards, [first, second] if first.rank() == second.rank()),
+        }
+    }
+}
+impl Default for Hand {
+    fn default() -> Self {
+        Hand::new()
+    }
 }
 impl Hand {
     pub fn new() -> Self {
         Hand {
             state: HandState::Idle,
             cards: Some(Vec::<Card>::new()),
+            wager: Money::ZERO,
+            doubled: false,
+            split: false,
         }
     }
     /**
@@ -26,45 +74,180 @@ impl Hand {
     pub fn count(&mut self) -> usize {
         self.cards.as_ref().unwrap().len()
     }
-    pub fn clear(&mut self) {
-        self.cards = Some(Vec::<Card>::new());
-    }
-    pub fn get_total(&mut self) -> (u8, u8) {
-        let mut total = (0, 0);
-        self.cards.iter().flatten().for_each(|card| {
-            let special = if card.face == "Ace" { 10 } else { 0 };
-            total.0 += card.value;
-            total.1 += card.value + special;
-        });
-        total
-    }
-    pub fn get_total_single(&mut self) -> u8 {
-        let total = self.get_total();
-        let (sum, special) = total;
-        if special < 22 && special > sum {
-            return special;
+    /**
+     * Empty the hand, handing back its cards for the discard tray
+     */
+    pub fn clear(&mut self) -> Vec<Card> {
+        let cards = self.cards.replace(Vec::<Card>::new()).unwrap_or_default();
+        self.wager = Money::ZERO;
+        self.doubled = false;
+        self.split = false;
+        cards
+    }
+    /**
+     * Check if the first two cards form a pair, either by face or by value
+     */
+    pub fn is_pair(&self, by_value: bool) -> bool {
+        match self.cards.as_deref() {
+            Some([first, second]) if by_value => first.value() == second.value(),
+            _ => self.value().pair,
         }
-        sum
     }
     /**
-     * Print hand total
+     * Check if this hand was split from a pair of aces
+     */
+    pub fn is_split_aces(&self) -> bool {
+        self.split
+            && self
+                .cards
+                .iter()
+                .flatten()
+                .next()
+                .is_some_and(|card| card.is_ace())
+    }
+    /**
+     * Total and classification of the hand
+     */
+    pub fn value(&self) -> HandValue {
+        HandValue::from_cards(self.cards.as_deref().unwrap_or_default(), self.split)
+    }
+    /**
+     * Total as shown to players, with both counts for a soft hand
      */
-    pub fn display_total(&mut self) {
-        let (sum, special) = self.get_total();
-        if special < 22 && special > sum {
-            println!("Total: {} or {}", sum, special);
+    pub fn total_text(&self) -> String {
+        let value = self.value();
+        if value.soft {
+            format!("{} or {}", value.total - 10, value.total)
         } else {
-            println!("Total: {}", sum)
+            value.total.to_string()
         }
     }
+}
+impl fmt::Display for Hand {
     /**
-     * Print hand
+     * Cards in short notation separated by spaces, e.g. "AS TD 5H", or with
+     * {:#} the long form separated by commas
      */
-    pub fn display(&mut self) {
-        if let Some(cards) = &self.cards {
-            cards.iter().for_each(|card| {
-                println!("{} of {}", card.face, card.suit);
-            });
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = if f.alternate() { ", " } else { " " };
+        for (i, card) in self.cards.iter().flatten().enumerate() {
+            if i > 0 {
+                write!(f, "{}", separator)?;
+            }
+            if f.alternate() {
+                write!(f, "{:#}", card)?;
+            } else {
+                write!(f, "{}", card)?;
+            }
         }
+        Ok(())
+    }
+}
+impl FromStr for Hand {
+    type Err = ParseCardError;
+    /**
+     * Parse cards in short notation separated by whitespace, e.g. "As Td 5h",
+     * or the long form separated by commas. Any comma or " of " means the
+     * long form, so a single "Ace of Spades" reads as one card
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let long_form = |s: &str| s.to_ascii_lowercase().contains(" of ");
+        let cards = if s.contains(',') || long_form(s) {
+            s.split(',')
+                .map(|card| {
+                    let card = card.trim();
+                    if card.contains(char::is_whitespace) && !long_form(card) {
+                        return Err(ParseCardError::SeveralCards(String::from(card)));
+                    }
+                    card.parse()
+                })
+                .collect::<Result<Vec<Card>, _>>()?
+        } else {
+            s.split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<Card>, _>>()?
+        };
+        let mut hand = Hand::new();
+        hand.cards = Some(cards);
+        Ok(hand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::card::{Rank, Suit};
+
+    #[test]
+    fn parses_short_notation() {
+        let hand: Hand = "As Td 5h".parse().unwrap();
+        assert_eq!(
+            hand.cards,
+            Some(vec![
+                Card::new(Rank::Ace, Suit::Spades),
+                Card::new(Rank::Ten, Suit::Diamonds),
+                Card::new(Rank::Five, Suit::Hearts),
+            ])
+        );
+        assert_eq!(hand.to_string(), "AS TD 5H");
+        assert_eq!("10h 10c".parse::<Hand>().unwrap().to_string(), "TH TC");
+    }
+
+    #[test]
+    fn reads_back_both_forms() {
+        let hand: Hand = "As Td 5h".parse().unwrap();
+        assert_eq!(hand.to_string().parse::<Hand>().unwrap(), hand);
+        let long = format!("{:#}", hand);
+        assert_eq!(long, "Ace of Spades, Ten of Diamonds, Five of Hearts");
+        assert_eq!(long.parse::<Hand>().unwrap(), hand);
+        let one: Hand = "Ace of Spades".parse().unwrap();
+        assert_eq!(one.cards, Some(vec![Card::new(Rank::Ace, Suit::Spades)]));
+        assert_eq!(format!("{:#}", one).parse::<Hand>().unwrap(), one);
+        assert_eq!(one.to_string().parse::<Hand>().unwrap(), one);
+    }
+
+    #[test]
+    fn reports_the_first_bad_card() {
+        assert_eq!(
+            "As Xd 5h".parse::<Hand>(),
+            Err(ParseCardError::UnknownRank(String::from("Xd")))
+        );
+        assert_eq!(
+            "Ace of Spades, Ten of Stars".parse::<Hand>(),
+            Err(ParseCardError::UnknownSuit(String::from("Ten of Stars")))
+        );
+        // A stray comma after short cards is a long-form hand with the
+        // wrong separator, not a card with an odd rank
+        assert_eq!(
+            "As Kd,".parse::<Hand>(),
+            Err(ParseCardError::SeveralCards(String::from("As Kd")))
+        );
+        assert_eq!("As, Kd,".parse::<Hand>(), Err(ParseCardError::Empty));
+    }
+
+    #[test]
+    fn only_an_unsplit_two_card_21_is_blackjack() {
+        let mut hand: Hand = "As Td".parse().unwrap();
+        let value = hand.value();
+        assert_eq!((value.total, value.soft, value.blackjack), (21, true, true));
+        hand.split = true;
+        let value = hand.value();
+        assert_eq!(
+            (value.total, value.soft, value.blackjack),
+            (21, true, false)
+        );
+        let three_cards: Hand = "7s 4d Th".parse().unwrap();
+        assert!(!three_cards.value().blackjack);
+    }
+
+    #[test]
+    fn classifies_soft_hard_bust_and_pairs() {
+        let value = |cards: &str| cards.parse::<Hand>().unwrap().value();
+        assert!(value("As 6d").soft);
+        assert_eq!(value("As 6d Td").total, 17);
+        assert!(!value("As 6d Td").soft);
+        assert!(value("Ks Qd 5h").bust);
+        assert!(value("8s 8d").pair);
+        assert!(!value("Ks Qd").pair);
     }
 }
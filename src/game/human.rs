@@ -0,0 +1,88 @@
+use crate::game::betting::BetSituation;
+use crate::game::money::Money;
+use crate::game::strategy::{Action, Situation, Strategy};
+use crate::game::ui::Ui;
+
+/**
+ * A person at the table, asked through the game's input
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HumanStrategy;
+impl HumanStrategy {
+    /**
+     * Ask a yes (y) or no (n) question
+     */
+    fn ask_yes_no(ui: &mut Ui, question: &str) -> bool {
+        ui.choose(question, &[('y', "yes"), ('n', "no")]) == Some('y')
+    }
+}
+impl Strategy for HumanStrategy {
+    fn wager(&mut self, bet: &BetSituation, ui: &mut Ui) -> Money {
+        loop {
+            let Some(response) = ui.ask(&format!(
+                "{}, how much would you like to wager? (bankroll {})",
+                bet.name, bet.bankroll
+            )) else {
+                return Money::ZERO;
+            };
+            let number = response.trim().parse::<Money>();
+            if let Ok(wager) = number {
+                if wager < bet.rules.min_bet {
+                    ui.say(&format!("The minimum bet is {}", bet.rules.min_bet))
+                } else if wager > bet.rules.max_bet {
+                    ui.say(&format!("The maximum bet is {}", bet.rules.max_bet))
+                } else if wager > bet.bankroll {
+                    ui.say("You don't have that much to wager")
+                } else {
+                    return wager;
+                }
+            } else {
+                ui.say("Not a number, please try again");
+            }
+        }
+    }
+    fn insurance(&mut self, situation: &Situation, max: Money, ui: &mut Ui) -> Money {
+        loop {
+            let Some(response) = ui.ask(&format!(
+                "{}, how much insurance would you like? ($0 to {})",
+                situation.name, max
+            )) else {
+                return Money::ZERO;
+            };
+            let number = response.trim().parse::<Money>();
+            if let Ok(amount) = number {
+                if amount > max {
+                    ui.say(&format!("Insurance must be between $0 and {}", max))
+                } else {
+                    return amount;
+                }
+            } else {
+                ui.say("Not a number, please try again");
+            }
+        }
+    }
+    fn even_money(&mut self, situation: &Situation, ui: &mut Ui) -> bool {
+        let question = format!("{}, would you like even money?", situation.name);
+        HumanStrategy::ask_yes_no(ui, &question)
+    }
+    fn surrender(&mut self, situation: &Situation, ui: &mut Ui) -> bool {
+        let question = format!("{}, would you like to surrender?", situation.name);
+        HumanStrategy::ask_yes_no(ui, &question)
+    }
+    fn action(&mut self, situation: &Situation, ui: &mut Ui) -> Action {
+        let mut options = vec![('h', "hit"), ('s', "stand")];
+        if situation.can_double {
+            options.push(('d', "double"));
+        }
+        if situation.can_split {
+            options.push(('p', "split"));
+        }
+        if situation.can_surrender {
+            options.push(('r', "surrender"));
+        }
+        match ui.choose(&format!("{}:", situation.name), &options) {
+            Some(key) => Action::from_key(key).expect("chose an unknown action"),
+            None => Action::Stand,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::game::money::Money;
+
+use std::collections::HashMap;
+
+/**
+ * The reason chips moved between a player and the house
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionKind {
+    Wager,
+    Double,
+    Split,
+    Insurance,
+    Payout,
+    InsurancePayout,
+    Surrender,
+    // Everything moved in a round that was called off, handed back
+    Refund,
+}
+impl TransactionKind {
+    /**
+     * Chips put at risk, which count towards the action
+     */
+    pub fn is_bet(self) -> bool {
+        matches!(
+            self,
+            TransactionKind::Wager
+                | TransactionKind::Double
+                | TransactionKind::Split
+                | TransactionKind::Insurance
+        )
+    }
+}
+
+/**
+ * A single movement of chips. Amounts are from the player's side: negative
+ * when the player pays the house, positive when the house pays the player
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub round: i64,
+    pub player: String,
+    pub kind: TransactionKind,
+    pub amount: Money,
+}
+
+/**
+ * The books of the table. Running totals cover the whole session, while
+ * the transactions themselves are only kept for the current round so a
+ * long session doesn't slow down as it goes
+ */
+#[derive(Debug)]
+pub struct Ledger {
+    pub round: i64,
+    // This round's movements, cleared when the next round starts
+    pub transactions: Vec<Transaction>,
+    // Net amount each player has won or lost
+    pub players: HashMap<String, Money>,
+    pub house: Money,
+    // Net amount the house has won this round
+    pub round_house: Money,
+    pub action: Money,
+}
+impl Default for Ledger {
+    fn default() -> Self {
+        Ledger::new()
+    }
+}
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger {
+            round: 0,
+            transactions: Vec::<Transaction>::new(),
+            players: HashMap::new(),
+            house: Money::ZERO,
+            round_house: Money::ZERO,
+            action: Money::ZERO,
+        }
+    }
+    /**
+     * Start the books for a new round, dropping the last round's movements
+     */
+    pub fn start_round(&mut self, round: i64) {
+        self.round = round;
+        self.transactions.clear();
+        self.round_house = Money::ZERO;
+    }
+    /**
+     * Record a movement for the current round
+     */
+    pub fn record(&mut self, player: &str, kind: TransactionKind, amount: Money) {
+        match self.players.get_mut(player) {
+            Some(total) => *total += amount,
+            None => {
+                self.players.insert(String::from(player), amount);
+            }
+        }
+        self.house -= amount;
+        self.round_house -= amount;
+        if kind.is_bet() {
+            self.action -= amount;
+        }
+        self.transactions.push(Transaction {
+            round: self.round,
+            player: String::from(player),
+            kind,
+            amount,
+        });
+    }
+    /**
+     * Undo a player's movements this round, for a round that is called
+     * off. Their bets no longer count towards the action. Returns the
+     * amount handed back to the player
+     */
+    pub fn refund(&mut self, player: &str) -> Money {
+        let mut net = Money::ZERO;
+        for transaction in self.transactions.iter().filter(|t| t.player == player) {
+            net += transaction.amount;
+            if transaction.kind.is_bet() {
+                self.action += transaction.amount;
+            }
+        }
+        self.record(player, TransactionKind::Refund, -net);
+        -net
+    }
+    /**
+     * Net amount a player has won (positive) or lost (negative)
+     */
+    pub fn player_total(&self, player: &str) -> Money {
+        self.players.get(player).copied().unwrap_or(Money::ZERO)
+    }
+    /**
+     * Net amount the house has won, the mirror of every player movement
+     */
+    pub fn house_total(&self) -> Money {
+        self.house
+    }
+    /**
+     * Total amount bet, including doubles, splits and insurance
+     */
+    pub fn total_action(&self) -> Money {
+        self.action
+    }
+    /**
+     * Net amount the house has won in the current round
+     */
+    pub fn round_total(&self) -> Money {
+        self.round_house
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_session_totals_across_rounds() {
+        let mut ledger = Ledger::new();
+        ledger.start_round(1);
+        ledger.record("Player 1", TransactionKind::Wager, Money::from_dollars(-10));
+        ledger.record("Player 2", TransactionKind::Wager, Money::from_dollars(-5));
+        ledger.record("Player 1", TransactionKind::Payout, Money::from_dollars(20));
+        assert_eq!(ledger.round_total(), Money::from_dollars(-5));
+        ledger.start_round(2);
+        assert!(ledger.transactions.is_empty());
+        assert_eq!(ledger.round_total(), Money::ZERO);
+        ledger.record("Player 1", TransactionKind::Wager, Money::from_dollars(-10));
+        ledger.record(
+            "Player 1",
+            TransactionKind::Insurance,
+            Money::from_dollars(-5),
+        );
+        ledger.record(
+            "Player 1",
+            TransactionKind::Surrender,
+            Money::from_dollars(5),
+        );
+        assert_eq!(ledger.round_total(), Money::from_dollars(10));
+        assert_eq!(ledger.player_total("Player 1"), Money::ZERO);
+        assert_eq!(ledger.player_total("Player 2"), Money::from_dollars(-5));
+        assert_eq!(ledger.player_total("Nobody"), Money::ZERO);
+        assert_eq!(ledger.house_total(), Money::from_dollars(5));
+        assert_eq!(ledger.total_action(), Money::from_dollars(30));
+    }
+}
@@ -1,27 +1,49 @@
+pub mod betting;
 pub mod card;
+pub mod chart;
+pub mod count;
+pub mod counting;
 pub mod dealer;
 pub mod deck;
 pub mod hand;
+pub mod human;
+pub mod ledger;
+pub mod money;
 pub mod player;
+pub mod render;
+pub mod rules;
+pub mod shoe;
+pub mod strategy;
+pub mod ui;
 
+use crate::game::betting::RandomBet;
+use crate::game::count::Count;
 use crate::game::dealer::Dealer;
+use crate::game::money::Money;
 use crate::game::player::Player;
-use std::{thread, time};
+use crate::game::rules::{HoleCardRule, ShoeMode, SurrenderRule, TableRules};
+use crate::game::shoe::ShoeError;
+use crate::game::strategy::{BasicStrategy, ChartStrategy};
+use crate::game::ui::{SeatView, TableView};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::time::Instant;
 
-const DELAY: u64 = 2;
-
-pub fn main() {
-    let mut game = Game::new();
-    game.run();
-}
-
-#[derive(Debug)]
-enum GameState {
+/**
+ * Each step of a round, run in order by the game loop
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameState {
     Idle,
     NewGame,
+    SetupPlayers,
     RoundStart,
     PlaceBets,
     DealHands,
+    EarlySurrender,
+    Insurance,
+    DealerPeek,
     PlayersTurn,
     DealerTurn,
     Payout,
@@ -29,22 +51,44 @@ enum GameState {
 }
 
 #[derive(Debug)]
-struct Game {
-    round_number: i64,
-    players: Option<Vec<Player>>,
-    dealer: Dealer,
-    state: GameState,
-    running: bool,
+pub struct Game {
+    pub round_number: i64,
+    pub players: Option<Vec<Player>>,
+    pub dealer: Dealer,
+    // Seeds the computer players' betting, alongside the shoe
+    pub rng: ChaCha8Rng,
+    pub state: GameState,
+    pub running: bool,
+    // Where a state that goes round the table has got to, so a step
+    // waiting for an answer picks up at the same seat and hand
+    pub seat: usize,
+    pub hand: Option<usize>,
+    // Bets decided so far this round, taken once every seat has one
+    pub wagers: Vec<Money>,
+    // When play began, for the rounds per hour in the summary
+    pub started: Instant,
+    // Charts for particular computer seats, numbered from 1, in place of
+    // the dealer's basic strategy
+    pub charts: HashMap<usize, BasicStrategy>,
 }
 impl Game {
-    pub fn new() -> Self {
-        Game {
+    /**
+     * Fails if the rules burn more cards than the shoe holds
+     */
+    pub fn new(rules: TableRules, seed: u64) -> Result<Self, ShoeError> {
+        Ok(Game {
             round_number: 0,
             players: Some(Vec::<Player>::new()),
-            dealer: Dealer::new(),
+            dealer: Dealer::new(rules, seed)?,
+            rng: ChaCha8Rng::seed_from_u64(seed.wrapping_add(1)),
             state: GameState::Idle,
             running: true,
-        }
+            seat: 0,
+            hand: None,
+            wagers: Vec::new(),
+            started: Instant::now(),
+            charts: HashMap::new(),
+        })
     }
     /**
      * Run the game
@@ -55,21 +99,50 @@ impl Game {
         self.game_loop();
     }
     /**
-     * The main game loop
+     * The main game loop, for input that answers as soon as it is asked.
+     * Input that waits for its answers steps the game itself
      */
     pub fn game_loop(&mut self) {
         while self.running {
-            match self.state {
-                GameState::Idle => {}
-                GameState::NewGame => self.new_game(),
-                GameState::RoundStart => self.round_start(),
-                GameState::PlaceBets => self.place_bets(),
-                GameState::DealHands => self.deal_hands(),
-                GameState::PlayersTurn => self.players_turn(),
-                GameState::DealerTurn => self.dealer_turn(),
-                GameState::Payout => self.payout(),
-                GameState::RoundEnd => self.round_end(),
-            }
+            self.step();
+        }
+    }
+    /**
+     * Run the current state, which moves the game on to the next, and
+     * show the table as it now stands. When a player's answer is still to
+     * come the step returns early with the question in `Ui::waiting`, and
+     * the same state asks it again the next time the game is stepped
+     */
+    pub fn step(&mut self) {
+        self.dealer.ui.waiting = None;
+        match self.state {
+            GameState::Idle => {}
+            GameState::NewGame => self.new_game(),
+            GameState::SetupPlayers => self.setup_players(),
+            GameState::RoundStart => self.round_start(),
+            GameState::PlaceBets => self.place_bets(),
+            GameState::DealHands => self.deal_hands(),
+            GameState::EarlySurrender => self.early_surrender(),
+            GameState::Insurance => self.insurance(),
+            GameState::DealerPeek => self.dealer_peek(),
+            GameState::PlayersTurn => self.players_turn(),
+            GameState::DealerTurn => self.dealer_turn(),
+            GameState::Payout => self.payout(),
+            GameState::RoundEnd => self.round_end(),
+        }
+        let table = self.table();
+        self.dealer.ui.update(|view| *view = table);
+        // Once the input runs out strategies fall back on answers that
+        // leave the table as it is, so a round under way is played out.
+        // The game stops before the next one takes any bets
+        let between_rounds = matches!(
+            self.state,
+            GameState::SetupPlayers | GameState::RoundStart | GameState::PlaceBets
+        );
+        if self.dealer.ui.closed && self.running && between_rounds {
+            self.dealer.ui.say("No more input, leaving the table\n");
+            self.summary();
+            self.running = false;
         }
     }
 
@@ -77,21 +150,51 @@ impl Game {
      * State methods
      */
     pub fn new_game(&mut self) {
-        println!("Welcome to blackjack.js\n");
-        self.dealer.create_decks();
-        self.dealer.shuffle_decks();
-        self.setup_players();
-        self.state = GameState::RoundStart;
+        self.dealer.ui.say("Welcome to blackjack.js\n");
+        self.dealer.ui.say(&format!("{}\n", self.dealer.rules));
+        self.dealer.ui.say(&format!(
+            "Seed {} (replay with --seed {})\n",
+            self.dealer.shoe.seed, self.dealer.shoe.seed
+        ));
+        self.dealer.shuffle();
+        self.started = Instant::now();
+        self.state = GameState::SetupPlayers;
     }
     pub fn round_start(&mut self) {
-        println!(
+        self.dealer.ui.say(&format!(
             "\n------------------ Round {}! ------------------\n",
             self.round_number + 1
-        );
+        ));
+        self.dealer.ledger.start_round(self.round_number + 1);
+        if self.dealer.shoe.needs_shuffle() {
+            self.dealer.shuffle();
+        }
+        self.dealer.ui.say(&format!("{} cards left in the shoe\n", self.dealer.shoe.remaining()));
+        if self.dealer.show_count {
+            for count in &self.dealer.counts {
+                self.dealer.ui.say(&count.to_string());
+            }
+            self.dealer.ui.say("");
+        }
+        self.dealer.ui.say("Place your bets\n");
+        self.wagers.clear();
         self.state = GameState::PlaceBets;
     }
     pub fn place_bets(&mut self) {
-        println!("Place your bets\n");
+        // Every bet is decided before any is taken, so the game can stop
+        // here without chips left on the table
+        let players = self.players.as_mut().expect("players should not be empty");
+        let decided = each_seat(players, &mut self.seat, |_, player| {
+            let Some(wager) = self.dealer.ask_wager(player) else {
+                return false;
+            };
+            self.wagers.push(wager);
+            true
+        });
+        if !decided {
+            return;
+        }
+        let wagers = std::mem::take(&mut self.wagers);
         // This is the rust way of looping over an option of Vec<T>
         // The rust iterators are very pewerful
         self.players
@@ -101,51 +204,117 @@ impl Game {
             .flatten()
             // Filtering on all active players
             .filter(|player| player.active)
+            .zip(wagers)
             // For each playaer, do xxx
-            .for_each(|player| {
-                if player.human {
-                    self.dealer.ask_wager(player);
-                } else {
-                    player.computer_wager();
-                }
-                println!("{} will wager ${}", player.name, player.wager);
+            .for_each(|(player, wager)| {
+                self.dealer.place_wager(player, wager);
+                self.dealer.ui.say(&format!("{} will wager {}", player.name, player.total_wager()));
             });
         self.state = GameState::DealHands;
     }
     pub fn deal_hands(&mut self) {
-        println!("\nDealing hands...\n");
+        self.dealer.ui.say("\nDealing hands...\n");
+        if let Err(error) = self.deal_first_cards() {
+            self.call_off_round(error);
+            return;
+        }
+        self.state = GameState::EarlySurrender;
+    }
+    fn deal_first_cards(&mut self) -> Result<(), ShoeError> {
         // Deal the first card
-        self.players
-            .iter_mut()
-            .flatten()
-            .filter(|player| player.active)
-            .for_each(|player| self.dealer.deal_card(player));
+        for player in self.players.iter_mut().flatten().filter(|player| player.active) {
+            self.dealer.deal_card(&mut player.hands[0])?;
+        }
         // A card for the dealer
-        self.dealer.dealer_card();
+        self.dealer.dealer_card()?;
+        for player in self.players.iter_mut().flatten().filter(|player| player.active) {
+            self.dealer.deal_card(&mut player.hands[0])?;
+        }
+        // European tables deal the second dealer card after the players act
+        if self.dealer.rules.hole_card_rule == HoleCardRule::American {
+            self.dealer.deal_hole_card()?;
+        }
+        Ok(())
+    }
+    pub fn early_surrender(&mut self) {
+        if self.dealer.rules.surrender_rule == SurrenderRule::Early {
+            let players = self.players.as_mut().expect("players should not be empty");
+            if !each_seat(players, &mut self.seat, |_, player| {
+                self.dealer.offer_early_surrender(player)
+            }) {
+                return;
+            }
+        }
+        if self.dealer.upcard().is_ace() {
+            self.dealer.ui.say("Dealer shows an Ace. Insurance?\n");
+        }
+        self.state = GameState::Insurance;
+    }
+    pub fn insurance(&mut self) {
+        if self.dealer.upcard().is_ace() {
+            let players = self.players.as_mut().expect("players should not be empty");
+            if !each_seat(players, &mut self.seat, |_, player| self.dealer.offer_insurance(player)) {
+                return;
+            }
+            self.dealer.ui.say("");
+        }
+        self.state = GameState::DealerPeek;
+    }
+    pub fn dealer_peek(&mut self) {
+        let blackjack = self.dealer.peek();
+        if self.dealer.rules.hole_card_rule == HoleCardRule::American {
+            self.players
+                .iter_mut()
+                .flatten()
+                .filter(|player| player.active)
+                .for_each(|player| self.dealer.settle_insurance(player));
+        }
+        if !blackjack {
+            self.dealer.ui.say("Players turn...\n");
+            self.state = GameState::PlayersTurn;
+            return;
+        }
+        // The round is over before anyone acts
         self.players
             .iter_mut()
             .flatten()
             .filter(|player| player.active)
-            .for_each(|player| self.dealer.deal_card(player));
-        self.state = GameState::PlayersTurn;
+            .for_each(|player| self.dealer.settle_dealer_blackjack(player));
+        self.state = GameState::Payout;
     }
     pub fn players_turn(&mut self) {
-        println!("Players turn...\n");
-        self.players
-            .iter_mut()
-            .flatten()
-            .filter(|player| player.active)
-            .for_each(|player| self.dealer.player_turn(player));
-        self.state = GameState::DealerTurn;
+        let mut error = None;
+        let players = self.players.as_mut().expect("players should not be empty");
+        let played = each_seat(players, &mut self.seat, |_, player| {
+            match self.dealer.player_turn(player, &mut self.hand) {
+                Ok(played) => played,
+                Err(shoe) => {
+                    error = Some(shoe);
+                    false
+                }
+            }
+        });
+        if let Some(error) = error {
+            self.call_off_round(error);
+        } else if played {
+            self.state = GameState::DealerTurn;
+        }
     }
     pub fn dealer_turn(&mut self) {
-        println!("Dealer's turn...\n");
-        self.dealer.dealer_turn();
+        self.dealer.ui.say("Dealer's turn...\n");
+        if let Err(error) = self.dealer.dealer_turn() {
+            self.call_off_round(error);
+            return;
+        }
         self.players
             .iter_mut()
             .flatten()
             .filter(|player| player.active)
-            .for_each(|player| self.dealer.hand_status(player));
+            .for_each(|player| {
+                // Without a hole card insurance is only decided now
+                self.dealer.settle_insurance(player);
+                self.dealer.hand_status(player);
+            });
         self.state = GameState::Payout;
     }
     pub fn payout(&mut self) {
@@ -155,8 +324,31 @@ impl Game {
             .filter(|player| player.active)
             .for_each(|player| {
                 self.dealer.payout(player);
-                println!("{} bankroll ${}", player.name, player.bankroll);
+                self.dealer.ui.say(&format!("{} bankroll {}", player.name, player.bankroll));
             });
+        let ledger = &self.dealer.ledger;
+        self.dealer.ui.say(&format!(
+            "\nHouse net {} this round, {} overall",
+            ledger.round_total(),
+            ledger.house_total()
+        ));
+        // Paused here, with the round's hands still on the table
+        self.dealer.ui.pause("Round over, press any key to continue");
+        self.state = GameState::RoundEnd;
+    }
+    /**
+     * Stop a round that can't be dealt to the end, handing every bet and
+     * payment made in it back
+     */
+    fn call_off_round(&mut self, error: ShoeError) {
+        self.dealer.ui.say(&format!("The round is called off, {}\n", error));
+        for player in self.players.iter_mut().flatten().filter(|player| player.active) {
+            self.dealer.refund(player);
+            self.dealer.ui.say(&format!("{} bankroll {}", player.name, player.bankroll));
+        }
+        self.seat = 0;
+        self.hand = None;
+        self.dealer.ui.pause("Round over, press any key to continue");
         self.state = GameState::RoundEnd;
     }
     pub fn round_end(&mut self) {
@@ -165,15 +357,27 @@ impl Game {
             .flatten()
             .filter(|player| player.active)
             .for_each(|player| {
-                if player.bankroll <= 5 {
-                    println!("{} has been eliminated", player.name);
+                // A player who can't cover the minimum bet is out
+                if player.bankroll < self.dealer.rules.min_bet {
+                    self.dealer.ui.say(&format!("{} has been eliminated", player.name));
                     self.dealer.remove_player(player);
                 }
-                // Clear player hand
-                player.hand.clear();
+                // Clear player hands
+                let cards = player.clear_hands();
+                self.dealer.shoe.discard(cards);
             });
         // Clear dealers hand
-        self.dealer.hand.clear();
+        let cards = self.dealer.hand.clear();
+        self.dealer.shoe.discard(cards);
+        if self.dealer.rules.shoe_mode == ShoeMode::Continuous {
+            self.dealer.shoe.continuous_shuffle();
+            let shoe = self.dealer.shoe.remaining();
+            self.dealer.counts.iter_mut().for_each(|count| count.reset(shoe));
+        }
+        // Every chip that moved must be on the books
+        debug_assert!(self.players.iter().flatten().all(|player| {
+            player.bankroll == player.buy_in + self.dealer.ledger.player_total(&player.name)
+        }));
         let players = self
             .players
             .as_ref()
@@ -182,26 +386,99 @@ impl Game {
             .filter(|x| x.active)
             .count();
         if players == 0 {
-            println!("There are no players remaining. Game over.\n");
-            std::process::exit(0);
+            self.dealer.ui.say("There are no players remaining. Game over.\n");
+            self.summary();
+            self.dealer.ui.pause("Game over, press any key to leave");
+            self.running = false;
+            return;
         }
         self.round_number += 1;
-        let sleep = time::Duration::from_millis(DELAY);
-        thread::sleep(sleep);
         self.state = GameState::RoundStart;
     }
 
+    /**
+     * Rounds played to the end. The last round is only counted into
+     * `round_number` once the next one starts
+     */
+    pub fn rounds_played(&self) -> i64 {
+        self.round_number + i64::from(self.state == GameState::RoundEnd)
+    }
+    /**
+     * Print session statistics for comparing table rules, whenever the
+     * game stops
+     */
+    pub fn summary(&mut self) {
+        let rounds = self.rounds_played();
+        let shuffles = self.dealer.shoe.shuffles;
+        let hours = self.started.elapsed().as_secs_f64() / 3600.0;
+        self.dealer.ui.say(&format!("Rounds played: {}", rounds));
+        self.dealer.ui.say(&format!("Shuffles: {}", shuffles));
+        if shuffles > 0 {
+            let per_shuffle = rounds as f64 / f64::from(shuffles);
+            self.dealer.ui.say(&format!("Rounds per shuffle: {:.1}", per_shuffle));
+        }
+        if hours > 0.0 {
+            let per_hour = rounds as f64 / hours;
+            self.dealer.ui.say(&format!("Rounds per hour: {:.0}", per_hour));
+        }
+        let ledger = &self.dealer.ledger;
+        let action = ledger.total_action();
+        self.dealer.ui.say(&format!("Total action: {}", action));
+        self.dealer.ui.say(&format!("House net: {}", ledger.house_total()));
+        if action > Money::ZERO {
+            let edge = ledger.house_total().cents() as f64 / action.cents() as f64;
+            self.dealer.ui.say(&format!("House edge: {:.2}%", edge * 100.0));
+        }
+    }
+    /**
+     * What the full-screen table shows between states
+     */
+    pub fn table(&self) -> TableView {
+        TableView {
+            round: self.round_number + 1,
+            rules: self.dealer.rules.name.clone(),
+            shoe: self.dealer.shoe.remaining(),
+            dealer: self.dealer.visible_cards(),
+            hole_card_hidden: self.dealer.hole_card_hidden,
+            counts: if self.dealer.show_count {
+                self.dealer.counts.iter().map(Count::to_string).collect()
+            } else {
+                Vec::new()
+            },
+            seats: self
+                .players
+                .iter()
+                .flatten()
+                .map(|player| SeatView::new(player, None))
+                .collect(),
+        }
+    }
     /**
      * Setup players by adding them to the game
      */
     pub fn setup_players(&mut self) {
-        let number = self.dealer.number_of_players();
-        for i in 0..number {
-            self.add_player(Player::new(String::from(format!("Player {}", i + 1))));
+        if self.players.as_ref().is_some_and(Vec::is_empty) {
+            let Some(number) = self.dealer.number_of_players() else {
+                return;
+            };
+            for i in 0..number {
+                self.add_player(Player::new(format!("Player {}", i + 1)));
+            }
         }
-        let players = self.players.as_mut().unwrap();
-        for player in players {
-            self.dealer.human_or_computer(player);
+        let players = self.players.as_mut().expect("players should not be empty");
+        let seated = each_seat(players, &mut self.seat, |seat, player| {
+            let Some(human) = self.dealer.is_human(player) else {
+                return false;
+            };
+            if !human {
+                let chart = self.charts.get(&(seat + 1)).unwrap_or(&self.dealer.strategy);
+                let betting = RandomBet::new(self.rng.gen());
+                player.strategy = Box::new(ChartStrategy::new(chart.clone(), Box::new(betting)));
+            }
+            true
+        });
+        if seated {
+            self.state = GameState::RoundStart;
         }
     }
     /**
@@ -214,3 +491,300 @@ impl Game {
             .push(player)
     }
 }
+
+/**
+ * Go round the active players from `seat`, stopping at the first whose
+ * `decide` is still waiting for an answer. True, with `seat` back at the
+ * first one, once every player has decided
+ */
+fn each_seat(
+    players: &mut [Player],
+    seat: &mut usize,
+    mut decide: impl FnMut(usize, &mut Player) -> bool,
+) -> bool {
+    while let Some(player) = players.get_mut(*seat) {
+        if player.active && !decide(*seat, player) {
+            return false;
+        }
+        *seat += 1;
+    }
+    *seat = 0;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::betting::BetSituation;
+    use crate::game::strategy::{Action, HandKind, Play, Situation, Strategy, UPCARDS};
+    use crate::game::ui::{GameOutput, PlayerInput, ScriptedInput, SilentOutput, Ui};
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    /**
+     * Answers given between steps, the way the full-screen table feeds
+     * its keys to the game
+     */
+    #[derive(Debug, Clone, Default)]
+    struct Keyboard(Rc<RefCell<VecDeque<String>>>);
+    impl PlayerInput for Keyboard {
+        fn ask(&mut self, _prompt: &str) -> Option<String> {
+            self.0.borrow_mut().pop_front()
+        }
+        fn choose(&mut self, _prompt: &str, _options: &[(char, &str)]) -> Option<char> {
+            self.0.borrow_mut().pop_front()?.chars().next()
+        }
+        fn waiting(&self) -> bool {
+            true
+        }
+    }
+
+    /**
+     * Keeps every message the game says
+     */
+    #[derive(Debug, Clone, Default)]
+    struct Transcript(Rc<RefCell<Vec<String>>>);
+    impl GameOutput for Transcript {
+        fn say(&mut self, message: &str) {
+            self.0.borrow_mut().push(String::from(message));
+        }
+    }
+
+    /**
+     * Stands on everything, noting how many dealer cards the table view
+     * shows at every decision
+     */
+    #[derive(Debug, Default)]
+    struct Spy {
+        dealer_cards: Rc<RefCell<Vec<usize>>>,
+    }
+    impl Spy {
+        fn look(&self, situation: &Situation, ui: &Ui) {
+            assert_eq!(ui.table.dealer.first(), Some(&situation.upcard));
+            self.dealer_cards.borrow_mut().push(ui.table.dealer.len());
+        }
+    }
+    impl Strategy for Spy {
+        fn wager(&mut self, bet: &BetSituation, _ui: &mut Ui) -> Money {
+            bet.rules.min_bet
+        }
+        fn insurance(&mut self, situation: &Situation, _max: Money, ui: &mut Ui) -> Money {
+            self.look(situation, ui);
+            Money::ZERO
+        }
+        fn surrender(&mut self, situation: &Situation, ui: &mut Ui) -> bool {
+            self.look(situation, ui);
+            false
+        }
+        fn action(&mut self, situation: &Situation, ui: &mut Ui) -> Action {
+            self.look(situation, ui);
+            Action::Stand
+        }
+    }
+
+    /**
+     * A game past setup, with the given seats and nothing shown
+     */
+    fn quiet_game(rules: TableRules, seed: u64, players: Vec<Player>) -> Game {
+        let mut game = Game::new(rules, seed).unwrap();
+        game.dealer.ui = Ui::new(Box::new(ScriptedInput::default()), Box::new(SilentOutput));
+        game.dealer.shuffle();
+        game.players = Some(players);
+        game.state = GameState::RoundStart;
+        game
+    }
+
+    #[test]
+    fn strategies_never_see_the_hole_card() {
+        for rules in [
+            TableRules::default(),
+            TableRules::preset("european").unwrap(),
+        ] {
+            let mut rules = rules;
+            rules.surrender_rule = SurrenderRule::Early;
+            let spy = Spy::default();
+            let seen = Rc::clone(&spy.dealer_cards);
+            let mut player = Player::new(String::from("Spy"));
+            player.strategy = Box::new(spy);
+            let mut game = quiet_game(rules, 11, vec![player]);
+            while game.running && game.round_number < 50 {
+                game.step();
+            }
+            let seen = seen.borrow();
+            assert!(!seen.is_empty());
+            assert!(seen.iter().all(|&cards| cards == 1));
+        }
+    }
+
+    #[test]
+    fn a_scripted_game_stops_when_the_script_runs_out() {
+        // One human player bets $10 and stands on 20 against 18
+        let script = "1\nh\n10\ns\n";
+        let mut game = Game::new(TableRules::default(), 5).unwrap();
+        game.dealer.ui = Ui::new(
+            Box::new(ScriptedInput::from_script(script)),
+            Box::new(SilentOutput),
+        );
+        game.run();
+        assert!(!game.running);
+        assert_eq!(game.round_number, 1);
+        let player = &game.players.as_ref().unwrap()[0];
+        let ledger = &game.dealer.ledger;
+        assert_eq!(player.bankroll, Money::from_dollars(110));
+        assert_eq!(ledger.player_total("Player 1"), Money::from_dollars(10));
+        assert_eq!(player.bankroll, player.buy_in + ledger.player_total("Player 1"));
+        assert_eq!(ledger.house_total(), -ledger.player_total("Player 1"));
+    }
+
+    #[test]
+    fn a_round_under_way_is_played_out_when_the_script_runs_out() {
+        // The script ends before the player acts on 20 against 18, so they
+        // stand and are paid before the game stops
+        let mut game = Game::new(TableRules::default(), 5).unwrap();
+        game.dealer.ui = Ui::new(
+            Box::new(ScriptedInput::from_script("1\nh\n10\n")),
+            Box::new(SilentOutput),
+        );
+        game.run();
+        assert_eq!(game.round_number, 1);
+        let player = &game.players.as_ref().unwrap()[0];
+        assert_eq!(player.bankroll, Money::from_dollars(110));
+        assert_eq!(player.total_wager(), Money::ZERO);
+        assert_eq!(game.dealer.ledger.house_total(), Money::from_dollars(-10));
+    }
+
+    #[test]
+    fn no_bets_are_taken_when_the_script_runs_out_while_betting() {
+        // The computer player bets before the human has no answer
+        let mut game = Game::new(TableRules::default(), 5).unwrap();
+        game.dealer.ui = Ui::new(
+            Box::new(ScriptedInput::from_script("2\nc\nh\n")),
+            Box::new(SilentOutput),
+        );
+        game.run();
+        assert_eq!(game.state, GameState::PlaceBets);
+        for player in game.players.iter().flatten() {
+            assert_eq!(player.bankroll, Money::from_dollars(100));
+            assert_eq!(player.total_wager(), Money::ZERO);
+        }
+        assert_eq!(game.dealer.ledger.total_action(), Money::ZERO);
+    }
+
+    #[test]
+    fn a_waiting_game_asks_again_once_the_answer_is_in() {
+        // The same hand as the scripted game, answered between steps
+        let keyboard = Keyboard::default();
+        let mut game = Game::new(TableRules::default(), 5).unwrap();
+        game.dealer.ui = Ui::new(Box::new(keyboard.clone()), Box::new(SilentOutput));
+        game.state = GameState::NewGame;
+        let mut answers = VecDeque::from(["1", "h", "10", "s"]);
+        let mut questions = Vec::new();
+        while game.running && game.round_number < 1 {
+            game.step();
+            if let Some(question) = &game.dealer.ui.waiting {
+                questions.push(question.prompt.clone());
+                let answer = answers.pop_front().expect("asked one question too many");
+                keyboard.0.borrow_mut().push_back(String::from(answer));
+            }
+        }
+        assert_eq!(
+            questions,
+            [
+                "How many players are playing?",
+                "Player 1: are you",
+                "Player 1, how much would you like to wager? (bankroll $100)",
+                "Player 1:",
+            ]
+        );
+        assert_eq!(game.dealer.ledger.player_total("Player 1"), Money::from_dollars(10));
+        // With no answer the next bet is asked for again and again, and
+        // nothing moves
+        game.step();
+        assert_eq!(game.state, GameState::PlaceBets);
+        for _ in 0..3 {
+            game.step();
+            assert_eq!(game.state, GameState::PlaceBets);
+            assert!(game.dealer.ui.waiting.is_some());
+        }
+        assert!(game.running);
+        assert_eq!(game.dealer.ledger.total_action(), Money::from_dollars(10));
+        assert_eq!(game.players.as_ref().unwrap()[0].bankroll, Money::from_dollars(110));
+    }
+
+    #[test]
+    fn a_round_the_shoe_cant_finish_is_called_off_and_refunded() {
+        let mut game = quiet_game(TableRules::default(), 3, vec![Player::new(String::from("Spy"))]);
+        game.players.as_mut().unwrap()[0].strategy = Box::new(Spy::default());
+        game.step();
+        game.step();
+        assert_eq!(game.state, GameState::DealHands);
+        assert_eq!(game.dealer.ledger.total_action(), Money::from_dollars(5));
+        // Every card is somewhere other than the shoe or the discards
+        game.dealer.shoe.cards.truncate(2);
+        game.dealer.shoe.discards.clear();
+        game.step();
+        assert_eq!(game.state, GameState::RoundEnd);
+        let player = &game.players.as_ref().unwrap()[0];
+        assert_eq!(player.bankroll, Money::from_dollars(100));
+        let ledger = &game.dealer.ledger;
+        assert_eq!(ledger.player_total("Spy"), Money::ZERO);
+        assert_eq!(ledger.house_total(), Money::ZERO);
+        assert_eq!(ledger.total_action(), Money::ZERO);
+        game.step();
+        assert_eq!(game.state, GameState::RoundStart);
+    }
+
+    #[test]
+    fn the_summary_is_printed_when_the_script_runs_out() {
+        let transcript = Transcript::default();
+        let mut game = Game::new(TableRules::default(), 5).unwrap();
+        game.dealer.ui = Ui::new(
+            Box::new(ScriptedInput::from_script("1\nh\n10\ns\n")),
+            Box::new(transcript.clone()),
+        );
+        game.run();
+        assert_eq!(game.rounds_played(), 1);
+        let said = transcript.0.borrow();
+        for line in ["Rounds played: 1", "Shuffles: 1", "Rounds per shuffle: 1.0"] {
+            assert!(said.iter().any(|message| message == line), "missing {}", line);
+        }
+    }
+
+    #[test]
+    fn a_seat_with_its_own_chart_plays_it() {
+        // Seat 2 stands on everything, seat 1 plays basic strategy
+        let mut stand = BasicStrategy {
+            plays: HashMap::new(),
+        };
+        for kind in HandKind::ALL {
+            for total in kind.rows() {
+                for upcard in UPCARDS {
+                    stand.set(kind, total, upcard, Play::Stand);
+                }
+            }
+        }
+        let mut game = Game::new(TableRules::default(), 9).unwrap();
+        game.dealer.ui = Ui::new(
+            Box::new(ScriptedInput::from_script("2\nc\nc\n")),
+            Box::new(SilentOutput),
+        );
+        game.charts.insert(2, stand);
+        game.state = GameState::NewGame;
+        // Cards drawn past the first two of each hand
+        let mut drawn = [0, 0];
+        while game.running && game.round_number < 100 {
+            game.step();
+            if game.state != GameState::Payout {
+                continue;
+            }
+            for (seat, player) in game.players.iter().flatten().enumerate() {
+                for hand in &player.hands {
+                    drawn[seat] += hand.cards.as_ref().map_or(0, Vec::len).saturating_sub(2);
+                }
+            }
+        }
+        assert!(drawn[0] > 0);
+        assert_eq!(drawn[1], 0);
+    }
+}
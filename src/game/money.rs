@@ -0,0 +1,250 @@
+use std::fmt;
+use std::iter::Sum;
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/**
+ * How a table rounds payouts that don't come to a whole number of cents
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    // Pay to the cent
+    Exact,
+    // Round down to the nearest 50 cents, as casinos pay in chips
+    HalfDollar,
+}
+impl Rounding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "exact" => Some(Rounding::Exact),
+            "half-dollar" => Some(Rounding::HalfDollar),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * An amount of chips, stored as a whole number of cents so that 3:2 and 6:5
+ * payouts never lose a fraction
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+impl Money {
+    pub const ZERO: Money = Money(0);
+    /**
+     * The most an amount read from text may be, a trillion dollars, far
+     * past any table limit
+     */
+    pub const MAX: Money = Money(100_000_000_000_000);
+
+    pub fn from_dollars(dollars: i64) -> Self {
+        Money(dollars * 100)
+    }
+    pub fn cents(self) -> i64 {
+        self.0
+    }
+    /**
+     * Whole dollars, dropping any cents
+     */
+    pub fn dollars(self) -> i64 {
+        self.0 / 100
+    }
+    /**
+     * Multiply by numerator/denominator, rounding the result down according
+     * to the table's policy
+     */
+    pub fn ratio(self, numerator: i64, denominator: i64, rounding: Rounding) -> Self {
+        // Worked out in i128 so no amount or ratio can overflow, then held
+        // to what fits
+        let cents = i128::from(self.0) * i128::from(numerator) / i128::from(denominator);
+        let cents = match rounding {
+            Rounding::Exact => cents,
+            Rounding::HalfDollar => cents - cents.rem_euclid(50),
+        };
+        Money(cents.clamp(i64::MIN.into(), i64::MAX.into()) as i64)
+    }
+}
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.0 -= other.0;
+    }
+}
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+impl Mul<i64> for Money {
+    type Output = Money;
+    fn mul(self, times: i64) -> Money {
+        Money(self.0.saturating_mul(times))
+    }
+}
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |total, money| total + money)
+    }
+}
+impl fmt::Display for Money {
+    /**
+     * Whole amounts print as "$5", anything else as "$7.50"
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.abs();
+        if cents % 100 == 0 {
+            write!(f, "{}${}", sign, cents / 100)
+        } else {
+            write!(f, "{}${}.{:02}", sign, cents / 100, cents % 100)
+        }
+    }
+}
+
+/**
+ * Error for an amount that isn't dollars with at most two decimal places
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseMoneyError(String);
+impl fmt::Display for ParseMoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is not an amount of money", self.0)
+    }
+}
+impl std::error::Error for ParseMoneyError {}
+impl From<ParseIntError> for ParseMoneyError {
+    fn from(error: ParseIntError) -> Self {
+        ParseMoneyError(error.to_string())
+    }
+}
+impl FromStr for Money {
+    type Err = ParseMoneyError;
+    /**
+     * Parse "12", "12.5", "12.50" or "$12.50", up to `Money::MAX`
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseMoneyError(String::from(s));
+        let amount = s.trim();
+        let amount = amount.strip_prefix('$').unwrap_or(amount);
+        let (dollars, cents) = match amount.split_once('.') {
+            Some((dollars, cents)) => (dollars, cents),
+            None => (amount, ""),
+        };
+        if dollars.is_empty() || !dollars.chars().all(|c| c.is_ascii_digit()) {
+            return Err(error());
+        }
+        if cents.len() > 2 || !cents.chars().all(|c| c.is_ascii_digit()) {
+            return Err(error());
+        }
+        let cents = match cents.len() {
+            0 => 0,
+            1 => cents.parse::<i64>()? * 10,
+            _ => cents.parse::<i64>()?,
+        };
+        // Amounts too large for the cents to fit are rejected, not wrapped
+        dollars
+            .parse::<i64>()?
+            .checked_mul(100)
+            .and_then(|total| total.checked_add(cents))
+            .map(Money)
+            .filter(|amount| *amount <= Money::MAX)
+            .ok_or_else(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dollars(amount: &str) -> Money {
+        amount.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_dollars_and_cents() {
+        assert_eq!(dollars("12"), Money(1200));
+        assert_eq!(dollars("12.5"), Money(1250));
+        assert_eq!(dollars("12.05"), Money(1205));
+        assert_eq!(dollars(" $12.50 "), Money(1250));
+        assert_eq!(dollars("0"), Money::ZERO);
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        for amount in ["", "$", "abc", "-5", "5.123", "1.x", "5 dollars", ".50"] {
+            assert!(amount.parse::<Money>().is_err(), "{:?} parsed", amount);
+        }
+    }
+
+    #[test]
+    fn rejects_amounts_over_the_maximum() {
+        assert_eq!(dollars("1000000000000"), Money::MAX);
+        assert!("1000000000000.01".parse::<Money>().is_err());
+        assert!("92233720368547758.07".parse::<Money>().is_err());
+        assert!("99999999999999999999".parse::<Money>().is_err());
+    }
+
+    #[test]
+    fn payouts_on_large_amounts_dont_overflow() {
+        let max = Money::MAX;
+        assert_eq!(max.ratio(3, 2, Rounding::Exact), Money(150_000_000_000_000));
+        assert_eq!(
+            max.ratio(6, 5, Rounding::HalfDollar),
+            Money(120_000_000_000_000)
+        );
+        assert_eq!(max * 3, Money(300_000_000_000_000));
+        let huge = Money(i64::MAX);
+        assert_eq!(huge.ratio(3, 2, Rounding::Exact), huge);
+        assert_eq!(huge.ratio(1, 2, Rounding::Exact), Money(i64::MAX / 2));
+        assert_eq!(huge * 2, huge);
+        assert_eq!(-huge * 2, Money(i64::MIN));
+    }
+
+    #[test]
+    fn pays_ratios_to_the_cent() {
+        let five = Money::from_dollars(5);
+        let seven = Money::from_dollars(7);
+        assert_eq!(five.ratio(6, 5, Rounding::Exact), dollars("6"));
+        assert_eq!(seven.ratio(3, 2, Rounding::Exact), dollars("10.50"));
+        assert_eq!(seven.ratio(6, 5, Rounding::Exact), dollars("8.40"));
+        assert_eq!(seven.ratio(1, 2, Rounding::Exact), dollars("3.50"));
+    }
+
+    #[test]
+    fn half_dollar_tables_round_down() {
+        let five = Money::from_dollars(5);
+        let seven = Money::from_dollars(7);
+        assert_eq!(five.ratio(6, 5, Rounding::HalfDollar), dollars("6"));
+        assert_eq!(seven.ratio(3, 2, Rounding::HalfDollar), dollars("10.50"));
+        assert_eq!(seven.ratio(6, 5, Rounding::HalfDollar), dollars("8"));
+        assert_eq!(
+            dollars("3").ratio(6, 5, Rounding::HalfDollar),
+            dollars("3.50")
+        );
+    }
+
+    #[test]
+    fn displays_whole_dollars_without_cents() {
+        assert_eq!(Money::from_dollars(5).to_string(), "$5");
+        assert_eq!(dollars("7.5").to_string(), "$7.50");
+        assert_eq!((-dollars("2.5")).to_string(), "-$2.50");
+    }
+}
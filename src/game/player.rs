@@ -1,14 +1,18 @@
+use crate::game::card::Card;
 use crate::game::hand::Hand;
+use crate::game::money::Money;
+use crate::game::human::HumanStrategy;
+use crate::game::strategy::Strategy;
 
-use rand::Rng;
-
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub struct Player {
     pub name: String,
-    pub hand: Hand,
-    pub human: bool,
-    pub bankroll: i64,
-    pub wager: i64,
+    pub hands: Vec<Hand>,
+    // Makes the player's decisions, a person at the keyboard by default
+    pub strategy: Box<dyn Strategy>,
+    pub bankroll: Money,
+    pub buy_in: Money,
+    pub insurance: Money,
     pub active: bool,
 }
 impl Player {
@@ -16,61 +20,44 @@ impl Player {
         Player {
             active: true,
             name,
-            hand: Hand::new(),
-            human: true,
-            bankroll: 100,
-            wager: 0,
+            hands: vec![Hand::new()],
+            strategy: Box::new(HumanStrategy),
+            bankroll: Money::from_dollars(100),
+            buy_in: Money::from_dollars(100),
+            insurance: Money::ZERO,
         }
     }
-    pub fn set_pay(&mut self, amount: i64) {
+    pub fn set_pay(&mut self, amount: Money) {
         self.bankroll += amount;
     }
-    pub fn set_wager(&mut self, wager: i64) {
-        self.wager = wager;
-    }
-    pub fn clear_wager(&mut self) {
-        self.wager = 0;
-    }
-    pub fn computer_wager(&mut self) {
-        let mut rng = rand::thread_rng();
-        let pct: f64 = 0.05;
-        let max: i64 = 100;
-        let bet = self.bankroll as f64 * pct;
-        let mut upper = bet.floor() as i64;
-        if upper <= 5 {
-            upper = 10;
-        }
-        let mut wager = rng.gen_range(5..upper);
-        if wager > self.bankroll {
-            wager = self.bankroll;
-        }
-        self.set_wager(wager.min(max));
-    }
-    pub fn set_human(&mut self, is_human: bool) {
-        self.human = is_human;
-    }
-    pub fn human_action(&self) -> String {
-        println!("{}: hit (h) or stand (s)?", self.name);
-        let mut action: String = String::new();
-        // Get user input
-        std::io::stdin()
-            .read_line(&mut action)
-            .expect("unable to read line");
-        if !["h", "s"].contains(&action.as_str().to_lowercase().trim()) {
-            self.human_action();
-        }
-        action
-    }
-    pub fn computer_action(&mut self) -> String {
-        println!("{}: hit (h) or stand (s)?", self.name);
-        let mut _action: String = String::new();
-        let total = self.hand.get_total_single();
-        // TODO: implement strategy lookup from HashMap
-        if total < 17 {
-            _action = String::from("h");
+    /**
+     * Set the wager on the player's starting hand
+     */
+    pub fn set_wager(&mut self, wager: Money) {
+        self.hands[0].wager = wager;
+    }
+    /**
+     * Sum of the wagers across every hand
+     */
+    pub fn total_wager(&self) -> Money {
+        self.hands.iter().map(|hand| hand.wager).sum()
+    }
+    /**
+     * Name used when announcing a specific hand
+     */
+    pub fn hand_name(&self, index: usize) -> String {
+        if self.hands.len() > 1 {
+            format!("{} (hand {})", self.name, index + 1)
         } else {
-            _action = String::from("s");
+            self.name.clone()
         }
-        _action
+    }
+    /**
+     * Discard all hands, leaving a single empty hand for the next round
+     */
+    pub fn clear_hands(&mut self) -> Vec<Card> {
+        let cards = self.hands.iter_mut().flat_map(|hand| hand.clear()).collect();
+        self.hands = vec![Hand::new()];
+        cards
     }
 }
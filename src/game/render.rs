@@ -0,0 +1,138 @@
+use crate::game::card::{Card, Rank, Suit};
+
+use colored::Colorize;
+
+/**
+ * How cards are drawn in the terminal
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CardStyle {
+    // Boxed cards using Unicode box drawing and suit symbols
+    Unicode,
+    // Boxed cards using plain ASCII characters
+    Ascii,
+    // One "Ace of Hearts" line per card
+    Plain,
+}
+impl CardStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "unicode" => Some(CardStyle::Unicode),
+            "ascii" => Some(CardStyle::Ascii),
+            "plain" => Some(CardStyle::Plain),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Renderer {
+    pub style: CardStyle,
+    // Hearts and diamonds are drawn in red
+    pub color: bool,
+}
+impl Renderer {
+    /**
+     * Pick a style the terminal can show, honouring NO_COLOR and dumb
+     * terminals
+     */
+    pub fn detect() -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default()
+            .to_uppercase();
+        let style = if term == "dumb" {
+            CardStyle::Plain
+        } else if locale.contains("UTF-8") || locale.contains("UTF8") {
+            CardStyle::Unicode
+        } else {
+            CardStyle::Ascii
+        };
+        Renderer {
+            style,
+            color: std::env::var_os("NO_COLOR").is_none() && term != "dumb",
+        }
+    }
+    /**
+     * Draw cards side by side, with the last `hidden` cards face down
+     */
+    pub fn cards(&self, cards: &[Card], hidden: usize) -> String {
+        let shown = cards.len().saturating_sub(hidden);
+        if self.style == CardStyle::Plain {
+            let mut lines: Vec<String> = cards[..shown]
+                .iter()
+                .map(|card| format!("{:#}", card))
+                .collect();
+            lines.extend((shown..cards.len()).map(|_| String::from("Face down card")));
+            return lines.join("\n");
+        }
+        let mut faces: Vec<[String; 5]> = cards[..shown].iter().map(|card| self.face(*card)).collect();
+        faces.extend((shown..cards.len()).map(|_| self.back()));
+        (0..5)
+            .map(|row| {
+                faces
+                    .iter()
+                    .map(|face| face[row].as_str())
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    fn face(&self, card: Card) -> [String; 5] {
+        let rank = match card.rank() {
+            Rank::Ten => String::from("10"),
+            rank => rank.symbol().to_string(),
+        };
+        let suit = match (self.style, card.suit()) {
+            (CardStyle::Unicode, Suit::Clubs) => "♣",
+            (CardStyle::Unicode, Suit::Diamonds) => "♦",
+            (CardStyle::Unicode, Suit::Hearts) => "♥",
+            (CardStyle::Unicode, Suit::Spades) => "♠",
+            (_, Suit::Clubs) => "C",
+            (_, Suit::Diamonds) => "D",
+            (_, Suit::Hearts) => "H",
+            (_, Suit::Spades) => "S",
+        };
+        let paint = |text: String| {
+            let red = [Suit::Hearts, Suit::Diamonds].contains(&card.suit());
+            if self.color && red {
+                text.red().to_string()
+            } else {
+                text
+            }
+        };
+        let (top, side, bottom) = self.frame();
+        [
+            top,
+            format!("{}{}{}", side, paint(format!("{:<5}", rank)), side),
+            format!("{}{}{}", side, paint(format!("  {}  ", suit)), side),
+            format!("{}{}{}", side, paint(format!("{:>5}", rank)), side),
+            bottom,
+        ]
+    }
+    fn back(&self) -> [String; 5] {
+        let (top, side, bottom) = self.frame();
+        let pattern = if self.style == CardStyle::Unicode {
+            "░░░░░"
+        } else {
+            "#####"
+        };
+        let pattern = if self.color {
+            pattern.blue().to_string()
+        } else {
+            String::from(pattern)
+        };
+        let middle = format!("{}{}{}", side, pattern, side);
+        [top, middle.clone(), middle.clone(), middle, bottom]
+    }
+    fn frame(&self) -> (String, &'static str, String) {
+        if self.style == CardStyle::Unicode {
+            (String::from("┌─────┐"), "│", String::from("└─────┘"))
+        } else {
+            (String::from("+-----+"), "|", String::from("+-----+"))
+        }
+    }
+}
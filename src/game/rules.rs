@@ -0,0 +1,233 @@
+use crate::game::money::{Money, Rounding};
+
+use std::fmt;
+
+/**
+ * Which starting hands a player may double down on
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DoubleRule {
+    AnyTwo,
+    NineToEleven,
+    TenToEleven,
+}
+impl DoubleRule {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "any" => Some(DoubleRule::AnyTwo),
+            "9-11" => Some(DoubleRule::NineToEleven),
+            "10-11" => Some(DoubleRule::TenToEleven),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * American tables deal the dealer a face-down hole card up front, while
+ * European tables (ENHC) deal the second card only after the players act
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HoleCardRule {
+    American,
+    European,
+}
+
+/**
+ * Whether a player may forfeit half their wager, and if so whether before
+ * (early) or after (late) the dealer checks for blackjack
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurrenderRule {
+    None,
+    Late,
+    Early,
+}
+impl SurrenderRule {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(SurrenderRule::None),
+            "late" => Some(SurrenderRule::Late),
+            "early" => Some(SurrenderRule::Early),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Hand-shuffled shoes are dealt down to the cut card, while a continuous
+ * shuffling machine (CSM) takes the discards back after every round
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShoeMode {
+    HandShuffled,
+    Continuous,
+}
+
+/**
+ * Every house rule the game and the dealer play by
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRules {
+    pub name: String,
+    pub decks: u8,
+    // Fraction of the shoe dealt before the cut card
+    pub penetration: f64,
+    pub burn_cards: u8,
+    pub shoe_mode: ShoeMode,
+    pub dealer_hits_soft_17: bool,
+    // Blackjack pays numerator:denominator, e.g. (3, 2)
+    pub blackjack_pays: (i64, i64),
+    pub double_rule: DoubleRule,
+    pub double_after_split: bool,
+    // Pairs match on value (e.g. King and Ten) rather than face
+    pub split_by_value: bool,
+    // Maximum number of hands a player may split into
+    pub max_hands: usize,
+    pub hole_card_rule: HoleCardRule,
+    pub surrender_rule: SurrenderRule,
+    pub rounding: Rounding,
+    pub min_bet: Money,
+    pub max_bet: Money,
+    pub seats: u8,
+}
+impl Default for TableRules {
+    fn default() -> Self {
+        TableRules {
+            name: String::from("Standard"),
+            decks: 6,
+            penetration: 0.75,
+            burn_cards: 1,
+            shoe_mode: ShoeMode::HandShuffled,
+            dealer_hits_soft_17: false,
+            blackjack_pays: (3, 2),
+            double_rule: DoubleRule::AnyTwo,
+            double_after_split: true,
+            split_by_value: false,
+            max_hands: 4,
+            hole_card_rule: HoleCardRule::American,
+            surrender_rule: SurrenderRule::None,
+            rounding: Rounding::HalfDollar,
+            min_bet: Money::from_dollars(5),
+            max_bet: Money::from_dollars(100),
+            seats: 8,
+        }
+    }
+}
+impl TableRules {
+    pub fn vegas_strip() -> Self {
+        TableRules {
+            name: String::from("Vegas Strip"),
+            decks: 6,
+            surrender_rule: SurrenderRule::Late,
+            min_bet: Money::from_dollars(10),
+            max_bet: Money::from_dollars(500),
+            seats: 7,
+            ..TableRules::default()
+        }
+    }
+    pub fn downtown() -> Self {
+        TableRules {
+            name: String::from("Downtown"),
+            decks: 2,
+            penetration: 0.65,
+            dealer_hits_soft_17: true,
+            min_bet: Money::from_dollars(5),
+            max_bet: Money::from_dollars(200),
+            seats: 6,
+            ..TableRules::default()
+        }
+    }
+    pub fn atlantic_city() -> Self {
+        TableRules {
+            name: String::from("Atlantic City"),
+            decks: 8,
+            penetration: 0.8,
+            surrender_rule: SurrenderRule::Late,
+            min_bet: Money::from_dollars(10),
+            max_bet: Money::from_dollars(500),
+            seats: 7,
+            ..TableRules::default()
+        }
+    }
+    pub fn european() -> Self {
+        TableRules {
+            name: String::from("European"),
+            decks: 6,
+            double_rule: DoubleRule::NineToEleven,
+            double_after_split: false,
+            max_hands: 2,
+            hole_card_rule: HoleCardRule::European,
+            min_bet: Money::from_dollars(5),
+            max_bet: Money::from_dollars(200),
+            seats: 7,
+            ..TableRules::default()
+        }
+    }
+    /**
+     * Look up a preset by its command line name
+     */
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(TableRules::default()),
+            "vegas-strip" => Some(TableRules::vegas_strip()),
+            "downtown" => Some(TableRules::downtown()),
+            "atlantic-city" => Some(TableRules::atlantic_city()),
+            "european" => Some(TableRules::european()),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for TableRules {
+    /**
+     * Summary of the rules for the table banner
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let double = match self.double_rule {
+            DoubleRule::AnyTwo => "any two cards",
+            DoubleRule::NineToEleven => "9-11 only",
+            DoubleRule::TenToEleven => "10-11 only",
+        };
+        let hole_card = match self.hole_card_rule {
+            HoleCardRule::American => "dealer peeks",
+            HoleCardRule::European => "no hole card",
+        };
+        let surrender = match self.surrender_rule {
+            SurrenderRule::None => "no surrender",
+            SurrenderRule::Late => "late surrender",
+            SurrenderRule::Early => "early surrender",
+        };
+        let soft_17 = if self.dealer_hits_soft_17 {
+            "Dealer hits soft 17 (H17)"
+        } else {
+            "Dealer stands on soft 17 (S17)"
+        };
+        writeln!(f, "{} rules", self.name)?;
+        writeln!(
+            f,
+            "{} decks, blackjack pays {}:{}, {}, {}",
+            self.decks, self.blackjack_pays.0, self.blackjack_pays.1, hole_card, surrender
+        )?;
+        writeln!(f, "{}", soft_17)?;
+        match self.shoe_mode {
+            ShoeMode::HandShuffled => writeln!(
+                f,
+                "Cut card at {:.0}% penetration, {} card(s) burned",
+                self.penetration * 100.0,
+                self.burn_cards
+            )?,
+            ShoeMode::Continuous => writeln!(f, "Continuous shuffling machine")?,
+        }
+        writeln!(
+            f,
+            "Double on {}{}, split to {} hands",
+            double,
+            if self.double_after_split {
+                " and after splits"
+            } else {
+                ""
+            },
+            self.max_hands
+        )?;
+        write!(f, "Bets {} to {}", self.min_bet, self.max_bet)
+    }
+}
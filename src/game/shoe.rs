@@ -0,0 +1,198 @@
+use crate::game::card::Card;
+use crate::game::deck::Deck;
+use crate::game::rules::ShoeMode;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+/**
+ * Why the shoe can't be built or dealt from
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShoeError {
+    // Burning this many cards would leave nothing to deal
+    TooManyBurnCards { burn: u8, cards: usize },
+    // Every card is on the table, with no discards to reshuffle
+    Empty,
+}
+impl fmt::Display for ShoeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShoeError::TooManyBurnCards { burn, cards } => write!(
+                f,
+                "can't burn {} card(s) from a shoe of {}, burn fewer than the shoe holds",
+                burn, cards
+            ),
+            ShoeError::Empty => write!(f, "the shoe is empty and every card is on the table"),
+        }
+    }
+}
+
+/**
+ * Every deck at the table merged into one stack. A cut card placed at the
+ * configured penetration marks the last round before the shoe is shuffled
+ */
+#[derive(Debug)]
+pub struct Shoe {
+    pub cards: Vec<Card>,
+    // Cards played or burned since the last shuffle
+    pub discards: Vec<Card>,
+    pub mode: ShoeMode,
+    // Fraction of the shoe dealt before the cut card comes out
+    pub penetration: f64,
+    // Cards discarded face down after each shuffle
+    pub burn: u8,
+    // Number of cards left in the shoe when the cut card comes out
+    pub cut_card: usize,
+    pub cut_card_reached: bool,
+    pub shuffles: u32,
+    // Every shuffle comes from this seed, so a session can be replayed
+    pub seed: u64,
+    pub rng: ChaCha8Rng,
+}
+impl Shoe {
+    /**
+     * Fails if the burn would take every card in the shoe
+     */
+    pub fn new(
+        decks: u8,
+        mode: ShoeMode,
+        penetration: f64,
+        burn: u8,
+        seed: u64,
+    ) -> Result<Self, ShoeError> {
+        let cards: Vec<Card> = (0..decks)
+            .flat_map(|_| Deck::new().cards.unwrap_or_default())
+            .collect();
+        if usize::from(burn) >= cards.len() {
+            return Err(ShoeError::TooManyBurnCards {
+                burn,
+                cards: cards.len(),
+            });
+        }
+        Ok(Shoe {
+            cards,
+            discards: Vec::<Card>::new(),
+            mode,
+            penetration,
+            burn,
+            cut_card: 0,
+            cut_card_reached: false,
+            shuffles: 0,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        })
+    }
+    /**
+     * Gather the discards back into the shoe, shuffle, place the cut card
+     * and burn. A reshuffle in the middle of a round, with cards still on
+     * the table, always leaves at least one card to deal
+     */
+    pub fn shuffle(&mut self) {
+        self.mix();
+        let total = self.cards.len();
+        self.cut_card = total - (total as f64 * self.penetration).round() as usize;
+        self.cut_card_reached = false;
+        let burn = usize::from(self.burn).min(total.saturating_sub(1));
+        for _ in 0..burn {
+            if let Some(card) = self.cards.pop() {
+                self.discards.push(card);
+            }
+        }
+    }
+    /**
+     * A continuous shuffling machine takes the discards back after every
+     * round, without a cut card or burn
+     */
+    pub fn continuous_shuffle(&mut self) {
+        self.mix();
+    }
+    /**
+     * Return cards from the table to the discard tray
+     */
+    pub fn discard(&mut self, cards: Vec<Card>) {
+        self.discards.extend(cards);
+    }
+    /**
+     * Merge the discards into the shoe and Fisher-Yates shuffle: swap each
+     * card with one at or below it
+     */
+    fn mix(&mut self) {
+        self.cards.append(&mut self.discards);
+        for i in (1..self.cards.len()).rev() {
+            let j = self.rng.gen_range(0..=i);
+            self.cards.swap(i, j);
+        }
+        self.shuffles += 1;
+    }
+    /**
+     * Draw the top card. The current round is always finished, so an empty
+     * shoe is only reshuffled from the discards as a last resort. Fails
+     * when there are no discards either
+     */
+    pub fn draw(&mut self) -> Result<Card, ShoeError> {
+        if self.cards.is_empty() {
+            self.shuffle();
+        }
+        let card = self.cards.pop().ok_or(ShoeError::Empty)?;
+        if self.mode == ShoeMode::HandShuffled
+            && !self.cut_card_reached
+            && self.cards.len() <= self.cut_card
+        {
+            self.cut_card_reached = true;
+        }
+        Ok(card)
+    }
+    /**
+     * The cut card came out during the last round
+     */
+    pub fn needs_shuffle(&self) -> bool {
+        self.cut_card_reached
+    }
+    pub fn remaining(&self) -> usize {
+        self.cards.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_a_burn_of_the_whole_shoe() {
+        let shoe = Shoe::new(1, ShoeMode::HandShuffled, 0.75, 52, 1);
+        assert_eq!(
+            shoe.unwrap_err(),
+            ShoeError::TooManyBurnCards {
+                burn: 52,
+                cards: 52
+            }
+        );
+        assert!(Shoe::new(1, ShoeMode::HandShuffled, 0.75, 51, 1).is_ok());
+    }
+
+    #[test]
+    fn deals_every_card_then_fails_once_none_are_left() {
+        // A burn of all but one card still leaves one to deal after every
+        // reshuffle, until every card is held and none are discarded
+        let mut shoe = Shoe::new(1, ShoeMode::HandShuffled, 0.75, 51, 1).unwrap();
+        shoe.shuffle();
+        let mut held = Vec::new();
+        while let Ok(card) = shoe.draw() {
+            held.push(card);
+        }
+        assert_eq!(held.len(), 52);
+        assert_eq!(shoe.draw(), Err(ShoeError::Empty));
+    }
+
+    #[test]
+    fn a_seed_deals_the_same_cards_everywhere() {
+        // Pinned so a seed shared between players, platforms or releases
+        // replays the same shoe
+        let mut shoe = Shoe::new(6, ShoeMode::HandShuffled, 0.75, 1, 1).unwrap();
+        shoe.shuffle();
+        let cards: Vec<String> = (0..5).map(|_| shoe.draw().unwrap().to_string()).collect();
+        assert_eq!(cards, ["4H", "KC", "KH", "9S", "9C"]);
+    }
+}
@@ -0,0 +1,506 @@
+use crate::game::betting::{BetSituation, BettingStrategy};
+use crate::game::card::Card;
+use crate::game::count::Count;
+use crate::game::hand::Hand;
+use crate::game::money::Money;
+use crate::game::rules::{HoleCardRule, SurrenderRule, TableRules};
+use crate::game::ui::Ui;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/**
+ * Dealer upcards a chart covers, with an Ace as 11
+ */
+pub const UPCARDS: RangeInclusive<u8> = 2..=11;
+
+/**
+ * What a player does with a hand
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+}
+impl Action {
+    /**
+     * Key a player presses for the action
+     */
+    pub fn key(self) -> char {
+        match self {
+            Action::Hit => 'h',
+            Action::Stand => 's',
+            Action::Double => 'd',
+            Action::Split => 'p',
+            Action::Surrender => 'r',
+        }
+    }
+    pub fn from_key(key: char) -> Option<Self> {
+        match key {
+            'h' => Some(Action::Hit),
+            's' => Some(Action::Stand),
+            'd' => Some(Action::Double),
+            'p' => Some(Action::Split),
+            'r' => Some(Action::Surrender),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * What a player can see when deciding on a hand. The rest of the table,
+ * face-up cards only, is in `Ui::table`
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Situation<'a> {
+    pub name: &'a str,
+    pub hand: &'a Hand,
+    pub upcard: Card,
+    pub bankroll: Money,
+    pub rules: &'a TableRules,
+    // Every count the table keeps, one per system
+    pub counts: &'a [Count],
+    pub can_double: bool,
+    pub can_split: bool,
+    pub can_surrender: bool,
+}
+
+/**
+ * Every decision a seat makes, whether a person at the keyboard or a
+ * computer player. Computer players are free to ignore the `Ui`
+ */
+pub trait Strategy: fmt::Debug {
+    fn wager(&mut self, bet: &BetSituation, ui: &mut Ui) -> Money;
+    /**
+     * Insurance to take, up to `max`
+     */
+    fn insurance(&mut self, _situation: &Situation, _max: Money, _ui: &mut Ui) -> Money {
+        Money::ZERO
+    }
+    /**
+     * Whether to take 1:1 on a blackjack against an Ace right away
+     */
+    fn even_money(&mut self, _situation: &Situation, _ui: &mut Ui) -> bool {
+        false
+    }
+    /**
+     * Whether to surrender before the dealer checks for blackjack
+     */
+    fn surrender(&mut self, situation: &Situation, ui: &mut Ui) -> bool;
+    fn action(&mut self, situation: &Situation, ui: &mut Ui) -> Action;
+}
+
+/**
+ * A computer player following a chart, never taking insurance, and
+ * betting however it's told to
+ */
+#[derive(Debug)]
+pub struct ChartStrategy {
+    pub chart: BasicStrategy,
+    pub betting: Box<dyn BettingStrategy>,
+}
+impl ChartStrategy {
+    pub fn new(chart: BasicStrategy, betting: Box<dyn BettingStrategy>) -> Self {
+        ChartStrategy { chart, betting }
+    }
+}
+impl Strategy for ChartStrategy {
+    fn wager(&mut self, bet: &BetSituation, _ui: &mut Ui) -> Money {
+        self.betting.wager(bet)
+    }
+    fn surrender(&mut self, situation: &Situation, _ui: &mut Ui) -> bool {
+        self.chart
+            .play(situation.hand, situation.upcard, true)
+            .is_surrender()
+    }
+    fn action(&mut self, situation: &Situation, _ui: &mut Ui) -> Action {
+        self.chart.action(
+            situation.hand,
+            situation.upcard,
+            situation.can_double,
+            situation.can_split,
+            situation.can_surrender,
+        )
+    }
+}
+
+/**
+ * A basic strategy chart entry. Doubling and surrendering aren't always
+ * allowed, so those entries say what to do instead
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Play {
+    // H
+    Hit,
+    // S
+    Stand,
+    // Dh: double, otherwise hit
+    DoubleOrHit,
+    // Ds: double, otherwise stand
+    DoubleOrStand,
+    // P
+    Split,
+    // Rh: surrender, otherwise hit
+    SurrenderOrHit,
+    // Rs: surrender, otherwise stand
+    SurrenderOrStand,
+    // Rp: surrender, otherwise split
+    SurrenderOrSplit,
+}
+impl Play {
+    pub fn code(self) -> &'static str {
+        match self {
+            Play::Hit => "H",
+            Play::Stand => "S",
+            Play::DoubleOrHit => "Dh",
+            Play::DoubleOrStand => "Ds",
+            Play::Split => "P",
+            Play::SurrenderOrHit => "Rh",
+            Play::SurrenderOrStand => "Rs",
+            Play::SurrenderOrSplit => "Rp",
+        }
+    }
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "H" => Some(Play::Hit),
+            "S" => Some(Play::Stand),
+            "Dh" => Some(Play::DoubleOrHit),
+            "Ds" => Some(Play::DoubleOrStand),
+            "P" => Some(Play::Split),
+            "Rh" => Some(Play::SurrenderOrHit),
+            "Rs" => Some(Play::SurrenderOrStand),
+            "Rp" => Some(Play::SurrenderOrSplit),
+            _ => None,
+        }
+    }
+    pub fn is_surrender(self) -> bool {
+        matches!(
+            self,
+            Play::SurrenderOrHit | Play::SurrenderOrStand | Play::SurrenderOrSplit
+        )
+    }
+    /**
+     * The action to take, given what the table allows for this hand
+     */
+    pub fn action(self, can_double: bool, can_split: bool, can_surrender: bool) -> Action {
+        match self {
+            Play::Hit => Action::Hit,
+            Play::Stand => Action::Stand,
+            Play::DoubleOrHit if can_double => Action::Double,
+            Play::DoubleOrHit => Action::Hit,
+            Play::DoubleOrStand if can_double => Action::Double,
+            Play::DoubleOrStand => Action::Stand,
+            Play::Split if can_split => Action::Split,
+            Play::Split => Action::Hit,
+            Play::SurrenderOrHit if can_surrender => Action::Surrender,
+            Play::SurrenderOrHit => Action::Hit,
+            Play::SurrenderOrStand if can_surrender => Action::Surrender,
+            Play::SurrenderOrStand => Action::Stand,
+            Play::SurrenderOrSplit if can_surrender => Action::Surrender,
+            Play::SurrenderOrSplit if can_split => Action::Split,
+            Play::SurrenderOrSplit => Action::Hit,
+        }
+    }
+}
+
+/**
+ * The three sections of a chart. Hard and soft rows are keyed by total,
+ * pair rows by the value of one card with an Ace as 11
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandKind {
+    Hard,
+    Soft,
+    Pair,
+}
+impl HandKind {
+    pub const ALL: [HandKind; 3] = [HandKind::Hard, HandKind::Soft, HandKind::Pair];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HandKind::Hard => "hard",
+            HandKind::Soft => "soft",
+            HandKind::Pair => "pair",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        HandKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+    /**
+     * Rows a complete chart has. Smaller totals are always hit
+     */
+    pub fn rows(self) -> RangeInclusive<u8> {
+        match self {
+            HandKind::Hard => 5..=21,
+            HandKind::Soft => 13..=21,
+            HandKind::Pair => 2..=11,
+        }
+    }
+}
+
+/**
+ * A basic strategy chart, keyed on the kind of hand, its total and the
+ * dealer's upcard (2 to 11, with an Ace as 11)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicStrategy {
+    pub plays: HashMap<(HandKind, u8, u8), Play>,
+}
+impl BasicStrategy {
+    /**
+     * Work out the chart for a table. Starts from four or more decks with
+     * the dealer standing on soft 17 and doubling after splits allowed,
+     * then adjusts for the deck count, H17, no DAS, surrender and no hole
+     * card
+     */
+    pub fn new(rules: &TableRules) -> Self {
+        use HandKind::{Hard, Pair, Soft};
+        let mut chart = BasicStrategy {
+            plays: HashMap::new(),
+        };
+        // Each row reads against a dealer 2, 3, 4, 5, 6, 7, 8, 9, ten and Ace
+        for total in 5..=8 {
+            chart.row(Hard, total, "H  H  H  H  H  H  H  H  H  H");
+        }
+        chart.row(Hard, 9, "H  Dh Dh Dh Dh H  H  H  H  H");
+        chart.row(Hard, 10, "Dh Dh Dh Dh Dh Dh Dh Dh H  H");
+        chart.row(Hard, 11, "Dh Dh Dh Dh Dh Dh Dh Dh Dh H");
+        chart.row(Hard, 12, "H  H  S  S  S  H  H  H  H  H");
+        for total in 13..=16 {
+            chart.row(Hard, total, "S  S  S  S  S  H  H  H  H  H");
+        }
+        for total in 17..=21 {
+            chart.row(Hard, total, "S  S  S  S  S  S  S  S  S  S");
+        }
+        chart.row(Soft, 13, "H  H  H  Dh Dh H  H  H  H  H");
+        chart.row(Soft, 14, "H  H  H  Dh Dh H  H  H  H  H");
+        chart.row(Soft, 15, "H  H  Dh Dh Dh H  H  H  H  H");
+        chart.row(Soft, 16, "H  H  Dh Dh Dh H  H  H  H  H");
+        chart.row(Soft, 17, "H  Dh Dh Dh Dh H  H  H  H  H");
+        chart.row(Soft, 18, "S  Ds Ds Ds Ds S  S  H  H  H");
+        for total in 19..=21 {
+            chart.row(Soft, total, "S  S  S  S  S  S  S  S  S  S");
+        }
+        chart.row(Pair, 2, "P  P  P  P  P  P  H  H  H  H");
+        chart.row(Pair, 3, "P  P  P  P  P  P  H  H  H  H");
+        chart.row(Pair, 4, "H  H  H  P  P  H  H  H  H  H");
+        chart.row(Pair, 5, "Dh Dh Dh Dh Dh Dh Dh Dh H  H");
+        chart.row(Pair, 6, "P  P  P  P  P  H  H  H  H  H");
+        chart.row(Pair, 7, "P  P  P  P  P  P  H  H  H  H");
+        chart.row(Pair, 8, "P  P  P  P  P  P  P  P  P  P");
+        chart.row(Pair, 9, "P  P  P  P  P  S  P  P  S  S");
+        chart.row(Pair, 10, "S  S  S  S  S  S  S  S  S  S");
+        chart.row(Pair, 11, "P  P  P  P  P  P  P  P  P  P");
+
+        let single_deck = rules.decks == 1;
+        // Fewer decks make doubling more attractive
+        if rules.decks <= 2 {
+            chart.set(Hard, 9, 2, Play::DoubleOrHit);
+            chart.set(Hard, 11, 11, Play::DoubleOrHit);
+        }
+        if single_deck {
+            chart.row(Hard, 8, "H  H  H  Dh Dh H  H  H  H  H");
+            chart.row(Soft, 13, "H  H  Dh Dh Dh H  H  H  H  H");
+            chart.row(Soft, 14, "H  H  Dh Dh Dh H  H  H  H  H");
+            chart.row(Soft, 17, "Dh Dh Dh Dh Dh H  H  H  H  H");
+            chart.row(Soft, 18, "S  Ds Ds Ds Ds S  S  H  H  S");
+            chart.set(Soft, 19, 6, Play::DoubleOrStand);
+            chart.row(Pair, 3, "P  P  P  P  P  P  P  H  H  H");
+            chart.row(Pair, 4, "H  H  P  P  P  H  H  H  H  H");
+            chart.row(Pair, 6, "P  P  P  P  P  P  H  H  H  H");
+            chart.row(Pair, 7, "P  P  P  P  P  P  P  H  S  H");
+        }
+        if rules.dealer_hits_soft_17 {
+            chart.set(Hard, 11, 11, Play::DoubleOrHit);
+            chart.set(Soft, 18, 2, Play::DoubleOrStand);
+            chart.set(Soft, 18, 11, Play::Hit);
+            chart.set(Soft, 19, 6, Play::DoubleOrStand);
+        }
+        // Without DAS a split hand is worth less, so split less often
+        if !rules.double_after_split {
+            if single_deck {
+                chart.row(Pair, 2, "H  P  P  P  P  P  H  H  H  H");
+                chart.row(Pair, 3, "H  H  P  P  P  P  H  H  H  H");
+                chart.row(Pair, 6, "P  P  P  P  P  H  H  H  H  H");
+                chart.row(Pair, 7, "P  P  P  P  P  P  H  H  S  H");
+            } else {
+                chart.row(Pair, 2, "H  H  P  P  P  P  H  H  H  H");
+                chart.row(Pair, 3, "H  H  P  P  P  P  H  H  H  H");
+                chart.row(Pair, 6, "H  P  P  P  P  H  H  H  H  H");
+            }
+            chart.row(Pair, 4, "H  H  H  H  H  H  H  H  H  H");
+        }
+        if rules.surrender_rule != SurrenderRule::None {
+            chart.set(Hard, 16, 10, Play::SurrenderOrHit);
+            chart.set(Hard, 16, 11, Play::SurrenderOrHit);
+            if rules.decks > 2 {
+                chart.set(Hard, 15, 10, Play::SurrenderOrHit);
+                chart.set(Hard, 16, 9, Play::SurrenderOrHit);
+            }
+            if single_deck {
+                chart.set(Pair, 7, 10, Play::SurrenderOrStand);
+            }
+            if rules.dealer_hits_soft_17 {
+                chart.set(Hard, 15, 11, Play::SurrenderOrHit);
+                chart.set(Hard, 17, 11, Play::SurrenderOrStand);
+                chart.set(Pair, 8, 11, Play::SurrenderOrSplit);
+            }
+        }
+        // Giving up before the peek also saves half the bet against every
+        // dealer blackjack, so weak hands surrender against a ten or Ace
+        if rules.surrender_rule == SurrenderRule::Early {
+            for total in (5..=7).chain(12..=16) {
+                chart.set(Hard, total, 11, Play::SurrenderOrHit);
+            }
+            chart.set(Hard, 17, 11, Play::SurrenderOrStand);
+            for total in 14..=16 {
+                chart.set(Hard, total, 10, Play::SurrenderOrHit);
+            }
+            for card in [3, 6, 7] {
+                chart.set(Pair, card, 11, Play::SurrenderOrHit);
+            }
+            chart.set(Pair, 7, 10, Play::SurrenderOrHit);
+            chart.set(Pair, 8, 10, Play::SurrenderOrSplit);
+            chart.set(Pair, 8, 11, Play::SurrenderOrSplit);
+        }
+        // With no hole card, doubles and splits against a ten or Ace are
+        // lost in full to a dealer blackjack
+        if rules.hole_card_rule == HoleCardRule::European {
+            chart.set(Hard, 11, 10, Play::Hit);
+            chart.set(Hard, 11, 11, Play::Hit);
+            chart.set(Pair, 8, 10, Play::Hit);
+            chart.set(Pair, 8, 11, Play::Hit);
+            chart.set(Pair, 11, 11, Play::Hit);
+        }
+        chart
+    }
+    /**
+     * Fill a row from ten codes, against a dealer 2 through Ace
+     */
+    fn row(&mut self, kind: HandKind, total: u8, codes: &str) {
+        for (upcard, code) in UPCARDS.zip(codes.split_whitespace()) {
+            let play = Play::from_code(code).expect("unknown play in basic strategy");
+            self.set(kind, total, upcard, play);
+        }
+    }
+    pub fn set(&mut self, kind: HandKind, total: u8, upcard: u8, play: Play) {
+        self.plays.insert((kind, total, upcard), play);
+    }
+    /**
+     * The chart entry for a hand against the dealer's upcard. Pairs are
+     * only played as pairs while they can still be split
+     */
+    pub fn play(&self, hand: &Hand, upcard: Card, can_split: bool) -> Play {
+        let value = hand.value();
+        let cards = hand.cards.as_deref().unwrap_or_default();
+        let (kind, total) = match cards {
+            [first, second] if can_split && first.value() == second.value() => {
+                (HandKind::Pair, card_value(*first))
+            }
+            _ if value.soft => (HandKind::Soft, value.total),
+            _ => (HandKind::Hard, value.total),
+        };
+        let key = (kind, total, card_value(upcard));
+        self.plays
+            .get(&key)
+            .copied()
+            .unwrap_or(if total < 17 { Play::Hit } else { Play::Stand })
+    }
+    /**
+     * The basic strategy action for a hand, given what the table allows
+     */
+    pub fn action(
+        &self,
+        hand: &Hand,
+        upcard: Card,
+        can_double: bool,
+        can_split: bool,
+        can_surrender: bool,
+    ) -> Action {
+        self.play(hand, upcard, can_split)
+            .action(can_double, can_split, can_surrender)
+    }
+}
+
+/**
+ * A card's value as used in the chart, with an Ace as 11
+ */
+fn card_value(card: Card) -> u8 {
+    if card.is_ace() {
+        11
+    } else {
+        card.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * The chart's entry for `hand` against `upcard` under `rules`, which
+     * default to six decks
+     */
+    fn play(rules: TableRules, hand: &str, upcard: &str) -> Play {
+        let chart = BasicStrategy::new(&rules);
+        chart.play(&hand.parse().unwrap(), upcard.parse().unwrap(), true)
+    }
+
+    #[test]
+    fn starts_from_the_six_deck_s17_chart() {
+        let rules = TableRules::default;
+        assert_eq!(play(rules(), "As 7d", "2c"), Play::Stand);
+        assert_eq!(play(rules(), "As 7d", "Ac"), Play::Hit);
+        assert_eq!(play(rules(), "4h 4d", "5c"), Play::Split);
+        assert_eq!(play(rules(), "5h 6d", "Tc"), Play::DoubleOrHit);
+        assert_eq!(play(rules(), "5h 6d", "Ac"), Play::Hit);
+        assert_eq!(play(rules(), "2h 3d", "Ac"), Play::Hit);
+    }
+
+    #[test]
+    fn h17_doubles_soft_18_against_a_2_and_hits_it_against_an_ace() {
+        let h17 = || TableRules {
+            dealer_hits_soft_17: true,
+            ..TableRules::default()
+        };
+        assert_eq!(play(h17(), "As 7d", "2c"), Play::DoubleOrStand);
+        assert_eq!(play(h17(), "As 7d", "Ac"), Play::Hit);
+        assert_eq!(play(h17(), "5h 6d", "Ac"), Play::DoubleOrHit);
+    }
+
+    #[test]
+    fn no_das_stops_splitting_fours() {
+        let no_das = || TableRules {
+            double_after_split: false,
+            ..TableRules::default()
+        };
+        assert_eq!(play(no_das(), "4h 4d", "5c"), Play::Hit);
+        assert_eq!(play(no_das(), "4h 4d", "6c"), Play::Hit);
+    }
+
+    #[test]
+    fn early_surrender_gives_up_a_hard_5_against_an_ace() {
+        let early = || TableRules {
+            surrender_rule: SurrenderRule::Early,
+            ..TableRules::default()
+        };
+        assert_eq!(play(early(), "2h 3d", "Ac"), Play::SurrenderOrHit);
+        assert_eq!(play(early(), "2h 3d", "Tc"), Play::Hit);
+    }
+
+    #[test]
+    fn enhc_hits_11_against_a_ten() {
+        let enhc = || TableRules {
+            hole_card_rule: HoleCardRule::European,
+            ..TableRules::default()
+        };
+        assert_eq!(play(enhc(), "5h 6d", "Tc"), Play::Hit);
+        assert_eq!(play(enhc(), "5h 6d", "9c"), Play::DoubleOrHit);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::game::card::Card;
+use crate::game::hand::{Hand, HandState};
+use crate::game::money::Money;
+use crate::game::player::Player;
+use crate::game::render::Renderer;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::{thread, time};
+
+const DELAY: u64 = 2;
+
+/**
+ * Answers to the questions the game asks its players. None means there
+ * is no answer: either it is still to come, see `waiting`, or there are
+ * no more answers and the game stops
+ */
+pub trait PlayerInput {
+    /**
+     * A typed answer, such as an amount of money
+     */
+    fn ask(&mut self, prompt: &str) -> Option<String>;
+    /**
+     * One of several options, each picked by a single key
+     */
+    fn choose(&mut self, prompt: &str, options: &[(char, &str)]) -> Option<char>;
+    /**
+     * Whether a missing answer may still come. `Game::step` then returns
+     * and asks again the next time it runs. Input that answers as it is
+     * asked never waits
+     */
+    fn waiting(&self) -> bool {
+        false
+    }
+}
+
+/**
+ * A question the game is waiting for an answer to
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Question {
+    pub prompt: String,
+    // Keys to pick from and their labels, none for a typed answer
+    pub options: Vec<(char, String)>,
+}
+
+/**
+ * Everything that happens at the table, as the game reports it
+ */
+pub trait GameOutput {
+    /**
+     * A message for everyone at the table
+     */
+    fn say(&mut self, message: &str);
+    /**
+     * A hand with its total, the last `hidden` cards face down
+     */
+    fn show_hand(&mut self, _title: &str, _cards: &[Card], _hidden: usize, _total: &str) {}
+    /**
+     * The whole table, whenever something on it changes
+     */
+    fn show_table(&mut self, _table: &TableView) {}
+    /**
+     * A moment between rounds
+     */
+    fn pause(&mut self, _prompt: &str) {}
+}
+
+/**
+ * The game's input and output, along with the table as last shown
+ */
+pub struct Ui {
+    pub input: Box<dyn PlayerInput>,
+    pub output: Box<dyn GameOutput>,
+    pub table: TableView,
+    // The input ran out, so the game stops once the round is over
+    pub closed: bool,
+    // Asked during this step with no answer yet. The step returns early
+    // and asks again when the game is stepped once the answer is in
+    pub waiting: Option<Question>,
+}
+impl fmt::Debug for Ui {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Ui")
+            .field("table", &self.table)
+            .field("closed", &self.closed)
+            .field("waiting", &self.waiting)
+            .finish()
+    }
+}
+impl Ui {
+    pub fn new(input: Box<dyn PlayerInput>, output: Box<dyn GameOutput>) -> Self {
+        Ui {
+            input,
+            output,
+            table: TableView::default(),
+            closed: false,
+            waiting: None,
+        }
+    }
+    /**
+     * Play line by line on stdin and stdout
+     */
+    pub fn terminal(renderer: Renderer) -> Self {
+        Ui::new(Box::new(TerminalInput), Box::new(TerminalOutput(renderer)))
+    }
+    pub fn say(&mut self, message: &str) {
+        self.output.say(message);
+    }
+    /**
+     * None when there is no answer. If the input ran out callers answer
+     * for the player in whatever way leaves the table as it is. If the
+     * answer is still to come the question is kept in `waiting`, and the
+     * caller's answer is thrown away
+     */
+    pub fn ask(&mut self, prompt: &str) -> Option<String> {
+        let answer = self.input.ask(prompt);
+        if answer.is_none() {
+            self.unanswered(prompt, &[]);
+        }
+        answer
+    }
+    pub fn choose(&mut self, prompt: &str, options: &[(char, &str)]) -> Option<char> {
+        let key = self.input.choose(prompt, options);
+        if key.is_none() {
+            self.unanswered(prompt, options);
+        }
+        key
+    }
+    fn unanswered(&mut self, prompt: &str, options: &[(char, &str)]) {
+        if self.input.waiting() {
+            self.waiting = Some(Question {
+                prompt: String::from(prompt),
+                options: options
+                    .iter()
+                    .map(|(key, label)| (*key, String::from(*label)))
+                    .collect(),
+            });
+        } else {
+            self.closed = true;
+        }
+    }
+    pub fn show_hand(&mut self, title: &str, cards: &[Card], hidden: usize, total: &str) {
+        self.output.show_hand(title, cards, hidden, total);
+    }
+    /**
+     * Change what the table shows and show it again
+     */
+    pub fn update(&mut self, change: impl FnOnce(&mut TableView)) {
+        change(&mut self.table);
+        self.output.show_table(&self.table);
+    }
+    pub fn pause(&mut self, prompt: &str) {
+        self.output.pause(prompt);
+    }
+}
+
+/**
+ * Prompts on stdout, answers typed on stdin
+ */
+#[derive(Debug)]
+pub struct TerminalInput;
+impl PlayerInput for TerminalInput {
+    fn ask(&mut self, prompt: &str) -> Option<String> {
+        println!("{}", prompt);
+        read_line()
+    }
+    fn choose(&mut self, prompt: &str, options: &[(char, &str)]) -> Option<char> {
+        let labels: Vec<String> = options
+            .iter()
+            .map(|(key, label)| format!("{} ({})", label, key))
+            .collect();
+        let (last, rest) = labels.split_last().expect("nothing to choose from");
+        loop {
+            if rest.is_empty() {
+                println!("{} {}?", prompt, last);
+            } else {
+                println!("{} {} or {}?", prompt, rest.join(", "), last);
+            }
+            let response = read_line()?;
+            if let Some(key) = pick(&response, options) {
+                return Some(key);
+            }
+        }
+    }
+}
+
+/**
+ * Read a line from stdin, None once input runs out
+ */
+fn read_line() -> Option<String> {
+    let mut response = String::new();
+    match io::stdin().read_line(&mut response) {
+        Ok(0) => None,
+        Ok(_) => Some(response),
+        Err(error) => panic!("couldn't read line: {}", error),
+    }
+}
+
+/**
+ * The option whose key was typed, ignoring case and whitespace
+ */
+fn pick(response: &str, options: &[(char, &str)]) -> Option<char> {
+    let response = response.trim().to_lowercase();
+    options
+        .iter()
+        .map(|(key, _)| *key)
+        .find(|key| response == key.to_string())
+}
+
+/**
+ * Messages printed line by line, with hands drawn as cards
+ */
+#[derive(Debug)]
+pub struct TerminalOutput(pub Renderer);
+impl GameOutput for TerminalOutput {
+    fn say(&mut self, message: &str) {
+        println!("{}", message);
+    }
+    fn show_hand(&mut self, title: &str, cards: &[Card], hidden: usize, total: &str) {
+        println!("\n{} hand:", title);
+        println!("{}", self.0.cards(cards, hidden));
+        println!("Total: {}", total);
+    }
+    fn pause(&mut self, _prompt: &str) {
+        thread::sleep(time::Duration::from_millis(DELAY));
+    }
+}
+
+/**
+ * Canned answers given in order, for running the game without anyone at
+ * the keyboard. An answer that doesn't fit the question is skipped, just
+ * as a mistyped one is at the terminal
+ */
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedInput {
+    pub answers: VecDeque<String>,
+}
+impl ScriptedInput {
+    pub fn new<I, S>(answers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        ScriptedInput {
+            answers: answers.into_iter().map(Into::into).collect(),
+        }
+    }
+    /**
+     * One answer per line, ignoring blank lines and # comments
+     */
+    pub fn from_script(script: &str) -> Self {
+        ScriptedInput::new(
+            script
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or_default().trim())
+                .filter(|line| !line.is_empty()),
+        )
+    }
+}
+impl PlayerInput for ScriptedInput {
+    fn ask(&mut self, _prompt: &str) -> Option<String> {
+        self.answers.pop_front()
+    }
+    fn choose(&mut self, _prompt: &str, options: &[(char, &str)]) -> Option<char> {
+        loop {
+            if let Some(key) = pick(&self.answers.pop_front()?, options) {
+                return Some(key);
+            }
+        }
+    }
+}
+
+/**
+ * Output that shows nothing, for running the game headless
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentOutput;
+impl GameOutput for SilentOutput {
+    fn say(&mut self, _message: &str) {}
+}
+
+/**
+ * Everything the full-screen table draws
+ */
+#[derive(Debug, Clone, Default)]
+pub struct TableView {
+    pub round: i64,
+    pub rules: String,
+    pub shoe: usize,
+    // Face-up cards only. A hidden hole card is never on the table view,
+    // which strategies can read, so it is only flagged
+    pub dealer: Vec<Card>,
+    pub hole_card_hidden: bool,
+    // Only shown when asked for
+    pub counts: Vec<String>,
+    pub seats: Vec<SeatView>,
+}
+impl TableView {
+    /**
+     * Replace the seat with the same player name
+     */
+    pub fn set_seat(&mut self, seat: SeatView) {
+        match self.seats.iter_mut().find(|s| s.name == seat.name) {
+            Some(s) => *s = seat,
+            None => self.seats.push(seat),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SeatView {
+    pub name: String,
+    pub bankroll: Money,
+    pub active: bool,
+    pub hands: Vec<HandView>,
+    // The hand being played, while it is this seat's turn
+    pub playing: Option<usize>,
+}
+impl SeatView {
+    pub fn new(player: &Player, playing: Option<usize>) -> Self {
+        SeatView {
+            name: player.name.clone(),
+            bankroll: player.bankroll,
+            active: player.active,
+            hands: player.hands.iter().map(HandView::new).collect(),
+            playing,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HandView {
+    pub cards: Vec<Card>,
+    pub wager: Money,
+    pub total: String,
+    pub result: &'static str,
+}
+impl HandView {
+    pub fn new(hand: &Hand) -> Self {
+        let cards = hand.cards.clone().unwrap_or_default();
+        let result = match hand.state {
+            HandState::Idle => "",
+            HandState::Win => "Win",
+            HandState::Lose if hand.value().bust => "Bust",
+            HandState::Lose => "Lose",
+            HandState::Push => "Push",
+            HandState::Blackjack => "Blackjack",
+            HandState::Surrender => "Surrender",
+            HandState::EvenMoney => "Even money",
+        };
+        HandView {
+            total: if cards.is_empty() {
+                String::new()
+            } else {
+                hand.total_text()
+            },
+            cards,
+            wager: hand.wager,
+            result,
+        }
+    }
+}
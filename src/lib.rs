@@ -0,0 +1,19 @@
+/*!
+ * Blackjack engine: cards, shoe, table rules, the dealer and the game loop.
+ * Front ends drive a `Game` through the `PlayerInput` and `GameOutput`
+ * traits
+ */
+pub mod game;
+
+pub use game::betting::{BetSituation, BettingStrategy, CountBet, FlatBet, RandomBet};
+pub use game::card::{Card, Rank, Suit};
+pub use game::count::Count;
+pub use game::counting::CountingSystem;
+pub use game::dealer::Dealer;
+pub use game::hand::{Hand, HandState, HandValue};
+pub use game::human::HumanStrategy;
+pub use game::player::Player;
+pub use game::rules::TableRules;
+pub use game::strategy::{Action, BasicStrategy, ChartStrategy, Situation, Strategy};
+pub use game::ui::{GameOutput, PlayerInput, TableView};
+pub use game::{Game, GameState};
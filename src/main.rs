@@ -1,5 +1,271 @@
-mod game;
+mod tui;
+
+use crate::tui::SharedTui;
+use blackjack::game::count::Count;
+use blackjack::game::counting::{CountingSystem, SYSTEMS};
+use blackjack::game::money::Rounding;
+use blackjack::game::render::{CardStyle, Renderer};
+use blackjack::game::rules::{DoubleRule, HoleCardRule, ShoeMode, SurrenderRule, TableRules};
+use blackjack::game::strategy::BasicStrategy;
+use blackjack::game::ui::{ScriptedInput, Ui};
+use blackjack::game::Game;
+use rand::Rng;
+use std::collections::HashMap;
+use std::path::Path;
 
 fn main() {
-    game::main();
+    let options = parse_args();
+    if let Some(format) = &options.export {
+        let chart = BasicStrategy::new(&options.rules);
+        if format == "csv" {
+            print!("{}", chart.to_csv());
+        } else {
+            print!("{}", chart.to_toml());
+        }
+        return;
+    }
+    // Without a seed every session is different, but can still be replayed
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut game = Game::new(options.rules, seed).unwrap_or_else(|error| usage(&error.to_string()));
+    game.dealer.show_count = options.show_count;
+    if !options.counts.is_empty() {
+        let decks = game.dealer.rules.decks;
+        game.dealer.counts = options
+            .counts
+            .into_iter()
+            .map(|system| Count::new(system, decks))
+            .collect();
+    }
+    if let Some(chart) = options.strategy {
+        game.dealer.strategy = chart;
+    }
+    game.charts = options.charts;
+    let tui = options.tui.then(SharedTui::new);
+    game.dealer.ui = match &tui {
+        Some(tui) => Ui::new(Box::new(tui.clone()), Box::new(tui.clone())),
+        None => Ui::terminal(options.renderer),
+    };
+    if let Some(script) = options.script {
+        game.dealer.ui.input = Box::new(script);
+    }
+    // The full-screen table reads the keys itself and steps the game
+    match tui {
+        Some(tui) => tui.play(&mut game),
+        None => game.run(),
+    }
+}
+
+/**
+ * Everything that can be set from the command line
+ */
+struct Options {
+    rules: TableRules,
+    seed: Option<u64>,
+    renderer: Renderer,
+    // Play on a full-screen table instead of line by line
+    tui: bool,
+    // Answers read from a file instead of the keyboard
+    script: Option<ScriptedInput>,
+    // Chart computer players follow instead of basic strategy
+    strategy: Option<BasicStrategy>,
+    // Charts for particular computer seats, numbered from 1
+    charts: HashMap<usize, BasicStrategy>,
+    // Print basic strategy for the rules in this format and exit
+    export: Option<String>,
+    // Announce the counts each round
+    show_count: bool,
+    // Counting systems to keep side by side, Hi-Lo when none are given
+    counts: Vec<CountingSystem>,
+}
+
+/**
+ * Build the table rules from a preset (--rules) and individual overrides,
+ * along with the shuffle seed and how the table is shown
+ */
+fn parse_args() -> Options {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // The preset is the base for every other option, wherever it appears
+    let mut rules = match args.iter().position(|arg| arg == "--rules") {
+        Some(i) => {
+            let name = args.get(i + 1).cloned().unwrap_or_default();
+            TableRules::preset(&name).unwrap_or_else(|| {
+                usage(&format!(
+                    "Unknown rules: {} (standard, vegas-strip, downtown, atlantic-city, european)",
+                    name
+                ))
+            })
+        }
+        None => TableRules::default(),
+    };
+    let mut seed = None;
+    let mut renderer = Renderer::detect();
+    let mut tui = false;
+    let mut script = None;
+    let mut strategy = None;
+    let mut charts = HashMap::new();
+    let mut export = None;
+    let mut show_count = false;
+    let mut counts = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" => {
+                args.next();
+            }
+            "--cards" => {
+                let name = args.next().unwrap_or_default();
+                match CardStyle::from_name(&name) {
+                    Some(style) => renderer.style = style,
+                    None => usage(&format!(
+                        "Unknown card style: {} (unicode, ascii, plain)",
+                        name
+                    )),
+                }
+            }
+            "--no-color" => renderer.color = false,
+            "--tui" => tui = true,
+            "--show-count" => show_count = true,
+            "--count" => {
+                let name = args.next().unwrap_or_default();
+                let system = match CountingSystem::from_name(&name) {
+                    Some(system) => system,
+                    None if name.ends_with(".toml") => CountingSystem::load(Path::new(&name))
+                        .unwrap_or_else(|error| {
+                            usage(&format!("Couldn't load count {}: {}", name, error))
+                        }),
+                    None => usage(&format!(
+                        "Unknown count: {} ({} or a .toml tag table)",
+                        name,
+                        SYSTEMS.join(", ")
+                    )),
+                };
+                counts.push(system);
+            }
+            "--script" => {
+                let path = args.next().unwrap_or_default();
+                match std::fs::read_to_string(&path) {
+                    Ok(text) => script = Some(ScriptedInput::from_script(&text)),
+                    Err(error) => usage(&format!("Couldn't read script {}: {}", path, error)),
+                }
+            }
+            "--strategy" => {
+                // SEAT=PATH gives one computer seat its own chart
+                let arg = args.next().unwrap_or_default();
+                let seat = arg
+                    .split_once('=')
+                    .and_then(|(seat, path)| Some((seat.parse::<usize>().ok()?, path)));
+                let path = seat.map_or(arg.as_str(), |(_, path)| path);
+                let chart = BasicStrategy::load(Path::new(path)).unwrap_or_else(|error| {
+                    usage(&format!("Couldn't load strategy {}: {}", path, error))
+                });
+                match seat {
+                    Some((0, _)) => usage("--strategy seats are numbered from 1"),
+                    Some((seat, _)) => {
+                        charts.insert(seat, chart);
+                    }
+                    None => strategy = Some(chart),
+                }
+            }
+            "--export-strategy" => {
+                let format = args.next().unwrap_or_default();
+                match format.as_str() {
+                    "csv" | "toml" => export = Some(format),
+                    _ => usage(&format!("Unknown chart format: {} (csv, toml)", format)),
+                }
+            }
+            "--seed" => match args.next().and_then(|n| n.parse::<u64>().ok()) {
+                Some(n) => seed = Some(n),
+                _ => usage("--seed expects a whole number"),
+            },
+            "--decks" => match args.next().and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) if n > 0 => rules.decks = n,
+                _ => usage("--decks expects a number greater than 0"),
+            },
+            "--blackjack-pays" => {
+                let ratio = args.next().unwrap_or_default();
+                let parsed = ratio
+                    .split_once(':')
+                    .and_then(|(n, d)| Some((n.parse::<i64>().ok()?, d.parse::<i64>().ok()?)));
+                match parsed {
+                    Some((n, d)) if n > 0 && d > 0 => rules.blackjack_pays = (n, d),
+                    _ => usage("--blackjack-pays expects a ratio such as 3:2 or 6:5"),
+                }
+            }
+            "--rounding" => {
+                let name = args.next().unwrap_or_default();
+                match Rounding::from_name(&name) {
+                    Some(rounding) => rules.rounding = rounding,
+                    None => usage(&format!("Unknown rounding: {} (exact, half-dollar)", name)),
+                }
+            }
+            "--penetration" => match args.next().and_then(|n| n.parse::<f64>().ok()) {
+                Some(n) if n > 0.0 && n <= 1.0 => rules.penetration = n,
+                _ => usage("--penetration expects a fraction such as 0.75"),
+            },
+            "--csm" => rules.shoe_mode = ShoeMode::Continuous,
+            "--burn" => match args.next().and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) => rules.burn_cards = n,
+                _ => usage("--burn expects a number of cards"),
+            },
+            "--h17" => rules.dealer_hits_soft_17 = true,
+            "--s17" => rules.dealer_hits_soft_17 = false,
+            "--double" => {
+                let name = args.next().unwrap_or_default();
+                match DoubleRule::from_name(&name) {
+                    Some(rule) => rules.double_rule = rule,
+                    None => usage(&format!("Unknown double rule: {} (any, 9-11, 10-11)", name)),
+                }
+            }
+            "--split-by-value" => rules.split_by_value = true,
+            "--max-hands" => match args.next().and_then(|n| n.parse::<usize>().ok()) {
+                Some(n) if n > 0 => rules.max_hands = n,
+                _ => usage("--max-hands expects a number greater than 0"),
+            },
+            "--no-das" => rules.double_after_split = false,
+            "--enhc" => rules.hole_card_rule = HoleCardRule::European,
+            "--surrender" => {
+                let name = args.next().unwrap_or_default();
+                match SurrenderRule::from_name(&name) {
+                    Some(rule) => rules.surrender_rule = rule,
+                    None => usage(&format!(
+                        "Unknown surrender rule: {} (none, late, early)",
+                        name
+                    )),
+                }
+            }
+            _ => usage(&format!("Unknown option: {}", arg)),
+        }
+    }
+    if usize::from(rules.burn_cards) >= usize::from(rules.decks) * 52 {
+        usage(&format!(
+            "--burn expects fewer cards than the shoe's {}",
+            usize::from(rules.decks) * 52
+        ));
+    }
+    if let Some(seat) = charts.keys().find(|seat| **seat > usize::from(rules.seats)) {
+        usage(&format!(
+            "--strategy seat {} is past the table's {} seats",
+            seat, rules.seats
+        ));
+    }
+    Options {
+        rules,
+        seed,
+        renderer,
+        tui,
+        script,
+        strategy,
+        charts,
+        export,
+        show_count,
+        counts,
+    }
+}
+
+/**
+ * Print an error for a bad command line option and exit
+ */
+fn usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
 }
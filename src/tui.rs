@@ -0,0 +1,361 @@
+use blackjack::game::card::{Card, Rank, Suit};
+use blackjack::game::hand::HandValue;
+use blackjack::game::ui::{GameOutput, PlayerInput, Question, SeatView, TableView};
+use blackjack::{Game, GameState};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+// Messages kept for the log pane
+const LOG_LINES: usize = 200;
+
+/**
+ * Full-screen table: the dealer, every seat, the shoe, a log of what
+ * happened and an action bar answered with single key presses.
+ *
+ * The game never waits on a key here. It is stepped from `SharedTui::play`,
+ * which reads the keys for whatever the game is waiting on and steps it
+ * again with the answer
+ */
+pub struct Tui {
+    terminal: DefaultTerminal,
+    table: TableView,
+    screen: Screen,
+    // Answers read from the keyboard, not yet picked up by the game
+    answers: VecDeque<String>,
+    // A pause asked for during the last step
+    paused: Option<String>,
+    // Esc or Ctrl-C was pressed, so no more keys are read
+    left: bool,
+}
+impl fmt::Debug for Tui {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tui")
+            .field("table", &self.table)
+            .field("screen", &self.screen)
+            .field("answers", &self.answers)
+            .field("paused", &self.paused)
+            .field("left", &self.left)
+            .finish()
+    }
+}
+impl Tui {
+    /**
+     * Switch the terminal to raw mode on the alternate screen
+     */
+    pub fn new() -> Self {
+        Tui {
+            terminal: ratatui::init(),
+            table: TableView::default(),
+            screen: Screen::default(),
+            answers: VecDeque::new(),
+            paused: None,
+            left: false,
+        }
+    }
+    pub fn log(&mut self, message: &str) {
+        let lines = message.lines().filter(|line| !line.trim().is_empty());
+        self.screen.log.extend(lines.map(String::from));
+        let overflow = self.screen.log.len().saturating_sub(LOG_LINES);
+        self.screen.log.drain(..overflow);
+        self.draw();
+    }
+    /**
+     * Read keys until the question is answered, with one of its option
+     * keys or with typed text and Enter. Nothing is answered once the
+     * player leaves
+     */
+    pub fn answer(&mut self, question: &Question) {
+        self.screen.prompt = question.prompt.clone();
+        self.screen.keys = question.options.clone();
+        if question.options.is_empty() {
+            self.screen.input = Some(String::new());
+        }
+        while let Some(key) = self.next_key() {
+            if question.options.is_empty() {
+                let input = self.screen.input.get_or_insert_with(String::new);
+                match key.code {
+                    KeyCode::Enter => {
+                        self.answers.push_back(std::mem::take(input));
+                        break;
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => {}
+                }
+            } else if let KeyCode::Char(c) = key.code {
+                let c = c.to_ascii_lowercase();
+                if question.options.iter().any(|(key, _)| *key == c) {
+                    self.answers.push_back(c.to_string());
+                    break;
+                }
+            }
+        }
+        self.screen.clear_prompt();
+    }
+    /**
+     * Wait for any key
+     */
+    pub fn pause(&mut self, prompt: &str) {
+        self.screen.prompt = String::from(prompt);
+        self.next_key();
+        self.screen.clear_prompt();
+    }
+    pub fn draw(&mut self) {
+        self.terminal
+            .draw(|frame| self.screen.render(frame, &self.table))
+            .expect("couldn't draw the table");
+    }
+    /**
+     * Draw the screen and block until a key is pressed. Esc or Ctrl-C
+     * leaves the game, after which this is always None and the game gets
+     * no more answers
+     */
+    fn next_key(&mut self) -> Option<KeyEvent> {
+        self.draw();
+        while !self.left {
+            let Event::Key(key) = event::read().expect("couldn't read a key") else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.code == KeyCode::Esc || ctrl_c {
+                self.left = true;
+            } else {
+                return Some(key);
+            }
+        }
+        None
+    }
+}
+/**
+ * A handle on the one screen, given to the game as both input and output
+ */
+#[derive(Debug, Clone)]
+pub struct SharedTui(Rc<RefCell<Tui>>);
+impl SharedTui {
+    pub fn new() -> Self {
+        SharedTui(Rc::new(RefCell::new(Tui::new())))
+    }
+    /**
+     * Play a game from the start: step it until it needs a key, read the
+     * key and step it on. The game stops itself once the player leaves
+     */
+    pub fn play(&self, game: &mut Game) {
+        game.state = GameState::NewGame;
+        while game.running {
+            game.step();
+            let mut tui = self.0.borrow_mut();
+            if let Some(prompt) = tui.paused.take() {
+                tui.pause(&prompt);
+            }
+            if let Some(question) = &game.dealer.ui.waiting {
+                tui.answer(question);
+            }
+        }
+    }
+}
+impl PlayerInput for SharedTui {
+    fn ask(&mut self, _prompt: &str) -> Option<String> {
+        self.0.borrow_mut().answers.pop_front()
+    }
+    fn choose(&mut self, _prompt: &str, _options: &[(char, &str)]) -> Option<char> {
+        let answer = self.0.borrow_mut().answers.pop_front()?;
+        answer.chars().next()
+    }
+    fn waiting(&self) -> bool {
+        !self.0.borrow().left
+    }
+}
+impl GameOutput for SharedTui {
+    fn say(&mut self, message: &str) {
+        self.0.borrow_mut().log(message);
+    }
+    fn show_table(&mut self, table: &TableView) {
+        let mut tui = self.0.borrow_mut();
+        tui.table = table.clone();
+        tui.draw();
+    }
+    fn pause(&mut self, prompt: &str) {
+        self.0.borrow_mut().paused = Some(String::from(prompt));
+    }
+}
+impl Drop for Tui {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+/**
+ * The log and action bar, which only the full-screen table has
+ */
+#[derive(Debug, Default)]
+struct Screen {
+    log: Vec<String>,
+    prompt: String,
+    keys: Vec<(char, String)>,
+    // Text typed so far, when the prompt wants more than a key
+    input: Option<String>,
+}
+impl Screen {
+    fn clear_prompt(&mut self) {
+        self.prompt.clear();
+        self.keys.clear();
+        self.input = None;
+    }
+    fn render(&self, frame: &mut Frame, table: &TableView) {
+        // Room for the bankroll line and two lines per hand
+        let hands = table
+            .seats
+            .iter()
+            .map(|seat| seat.hands.len())
+            .max()
+            .unwrap_or(1);
+        let [header, dealer, seats, log, action] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(4),
+            Constraint::Length(3 + 2 * hands as u16),
+            Constraint::Min(3),
+            Constraint::Length(3),
+        ])
+        .areas(frame.area());
+        let mut title = format!(
+            " Round {} · {} rules · {} cards in the shoe",
+            table.round, table.rules, table.shoe
+        );
+        for count in &table.counts {
+            title += &format!(" · {}", count);
+        }
+        frame.render_widget(
+            Paragraph::new(title).style(Style::new().add_modifier(Modifier::REVERSED)),
+            header,
+        );
+        self.render_dealer(frame, dealer, table);
+        self.render_seats(frame, seats, table);
+        let shown = (log.height as usize).saturating_sub(2);
+        let lines: Vec<Line> = self.log[self.log.len().saturating_sub(shown)..]
+            .iter()
+            .map(|line| Line::raw(line.as_str()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Log ")),
+            log,
+        );
+        self.render_action(frame, action);
+    }
+    fn render_dealer(&self, frame: &mut Frame, area: Rect, table: &TableView) {
+        let hidden = usize::from(table.hole_card_hidden);
+        let value = HandValue::from_cards(&table.dealer, false);
+        let mut lines = vec![Line::from(card_spans(&table.dealer, hidden))];
+        if !table.dealer.is_empty() {
+            lines.push(Line::raw(format!("Total: {}", value.total)));
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Dealer ")),
+            area,
+        );
+    }
+    fn render_seats(&self, frame: &mut Frame, area: Rect, table: &TableView) {
+        let count = table.seats.len().max(1) as u32;
+        let areas =
+            Layout::horizontal(vec![Constraint::Ratio(1, count); count as usize]).split(area);
+        for (seat, area) in table.seats.iter().zip(areas.iter()) {
+            frame.render_widget(seat_widget(seat), *area);
+        }
+    }
+    fn render_action(&self, frame: &mut Frame, area: Rect) {
+        let mut spans = vec![Span::raw(self.prompt.as_str())];
+        for (key, label) in &self.keys {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                format!("[{}]", key.to_ascii_uppercase()),
+                Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::raw(format!(" {}", label)));
+        }
+        if let Some(input) = &self.input {
+            spans.push(Span::raw(format!(" {}█", input)));
+        }
+        let block = Block::bordered()
+            .title(" Action ")
+            .title_bottom(Line::from(" Esc to leave ").right_aligned());
+        frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+    }
+}
+
+fn seat_widget(seat: &SeatView) -> Paragraph<'_> {
+    let mut lines = vec![Line::raw(format!("Bankroll {}", seat.bankroll))];
+    if !seat.active {
+        lines.push(Line::raw("Out"));
+    }
+    for (i, hand) in seat
+        .hands
+        .iter()
+        .enumerate()
+        .filter(|(_, hand)| !hand.cards.is_empty())
+    {
+        let marker = if seat.playing == Some(i) { "▶ " } else { "" };
+        let mut spans = vec![Span::raw(marker)];
+        spans.extend(card_spans(&hand.cards, 0));
+        lines.push(Line::from(spans));
+        let mut details = format!("Bet {} · {}", hand.wager, hand.total);
+        if !hand.result.is_empty() {
+            details = format!("{} · {}", details, hand.result);
+        }
+        lines.push(Line::raw(details));
+    }
+    let mut block = Block::bordered().title(format!(" {} ", seat.name));
+    if seat.playing.is_some() {
+        block = block.border_style(Style::new().fg(Color::Yellow));
+    } else if !seat.active {
+        block = block.border_style(Style::new().fg(Color::DarkGray));
+    }
+    Paragraph::new(lines).block(block)
+}
+
+/**
+ * Cards as short colored faces, followed by `hidden` card backs
+ */
+fn card_spans(cards: &[Card], hidden: usize) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    for (i, card) in cards.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" "));
+        }
+        let rank = match card.rank() {
+            Rank::Ten => String::from("10"),
+            rank => rank.symbol().to_string(),
+        };
+        let (suit, color) = match card.suit() {
+            Suit::Clubs => ("♣", Color::Reset),
+            Suit::Diamonds => ("♦", Color::Red),
+            Suit::Hearts => ("♥", Color::Red),
+            Suit::Spades => ("♠", Color::Reset),
+        };
+        spans.push(Span::styled(
+            format!("{}{}", rank, suit),
+            Style::new().fg(color).add_modifier(Modifier::BOLD),
+        ));
+    }
+    for _ in 0..hidden {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled("░░", Style::new().fg(Color::Blue)));
+    }
+    spans
+}